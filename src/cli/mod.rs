@@ -1,13 +1,105 @@
-use clap::Parser;
+pub mod repl;
+
+use {
+    clap::{ArgEnum, Parser},
+    roll::{Registry, DEFAULT_LIMIT},
+    std::path::PathBuf,
+};
+
+#[derive(ArgEnum, Debug, PartialEq, Clone, Copy)]
+pub enum Format {
+    Text,
+    Json,
+}
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 pub struct Args {
     content: Option<String>,
+    /// Show the exact probability distribution instead of rolling
+    #[clap(long)]
+    stats: bool,
+    /// Roll the expression this many times and chart the results
+    #[clap(long, value_name = "RUNS")]
+    simulate: Option<u64>,
+    /// Seed the dice, so the same expression and seed always roll the same
+    #[clap(long)]
+    seed: Option<u64>,
+    /// Show every die, reroll and explosion behind the result of a seeded roll
+    #[clap(long, requires = "seed")]
+    replay: bool,
+    /// How to print the result of a roll
+    #[clap(long, arg_enum, default_value = "text")]
+    format: Format,
+    /// Most rerolls or explosions of one die, for modifiers without a `{N}` limit of their own
+    #[clap(long, value_name = "N", default_value_t = DEFAULT_LIMIT, parse(try_from_str = parse_limit))]
+    limit: usize,
+    /// Roll expressions interactively, starting with any expression given, as happens when
+    /// no expression is given
+    #[clap(long)]
+    repl: bool,
+    /// TOML file of named dice, such as `[dice.hit] faces = [0, 0, 0, 1, 1, 2]` to roll `3dhit`,
+    /// or `[dice.force] symbols = ["y", "-", "sa"]` for dice of narrative symbols
+    #[clap(long, env = "ROLL_DICE", value_name = "FILE")]
+    dice: Option<PathBuf>,
+    /// Where interactive sessions keep their line history
+    #[clap(long, env = "ROLL_HISTORY", value_name = "FILE")]
+    history: Option<PathBuf>,
+}
+
+/// A limit of 0 would stop every reroll and explosion before it rolled, as `{0}` would.
+fn parse_limit(s: &str) -> Result<usize, String> {
+    match s.parse() {
+        Ok(0) => Err("the limit must be at least 1".to_string()),
+        Ok(limit) => Ok(limit),
+        Err(error) => Err(format!("{}", error)),
+    }
 }
 
 impl Args {
     pub fn content(&self) -> Option<&str> {
         self.content.as_ref().map(|s| &s[..])
     }
+
+    pub fn stats(&self) -> bool {
+        self.stats
+    }
+
+    pub fn simulate(&self) -> Option<u64> {
+        self.simulate
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn replay(&self) -> bool {
+        self.replay
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    pub fn repl(&self) -> bool {
+        self.repl || self.content.is_none()
+    }
+
+    /// The named dice file, if one was given or the default exists.
+    pub fn dice(&self) -> Option<PathBuf> {
+        self.dice
+            .clone()
+            .or_else(|| Registry::default_path().filter(|path| path.exists()))
+    }
+
+    /// The line history file, if one was given or there is a home directory to keep it in.
+    pub fn history(&self) -> Option<PathBuf> {
+        self.history
+            .clone()
+            .or_else(|| dirs::home_dir().map(|home| home.join(".roll_history")))
+    }
 }
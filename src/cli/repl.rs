@@ -0,0 +1,258 @@
+use {
+    anyhow::{anyhow, Result},
+    colored::Colorize,
+    rand::RngCore,
+    roll::{seeded, Evaluation, ParseError, Registry, RollParser},
+    rustyline::{error::ReadlineError, Editor},
+    std::{collections::BTreeMap, path::Path},
+};
+
+/// Words that dice notation uses on their own, such as the `b` of `1d100+b` or the `sw` of
+/// `sw(d8)`, which a variable would replace.
+const TOKENS: [&str; 24] = [
+    "b", "c", "d", "e", "f", "h", "k", "l", "m", "o", "p", "r", "t", "x", "sw", "kh", "kl", "dh",
+    "dl", "ro", "rh", "rl", "cs", "cf",
+];
+
+/// State carried between lines of an interactive session.
+pub struct Session<R: RngCore> {
+    rng: R,
+    limit: usize,
+    registry: Registry,
+    variables: BTreeMap<String, i32>,
+    last: Option<(String, Evaluation)>,
+}
+
+impl<R: RngCore> Session<R> {
+    pub fn new(rng: R, limit: usize) -> Self {
+        Self {
+            rng,
+            limit,
+            registry: Registry::default(),
+            variables: BTreeMap::new(),
+            last: None,
+        }
+    }
+
+    /// Read named dice such as `3dhit` from `registry`.
+    pub fn with_registry(mut self, registry: Registry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Act on one line of input, returning the text to show for it.
+    pub fn line(&mut self, line: &str) -> Result<Option<String>> {
+        let line = line.trim();
+        match line {
+            "" => Ok(None),
+            "last" => match &self.last {
+                Some((_, evaluation)) => Ok(Some(evaluation.to_string())),
+                None => Err(anyhow!("Nothing has been rolled yet")),
+            },
+            "again" => match &self.last {
+                Some((source, _)) => self.roll(&source.clone()).map(Some),
+                None => Err(anyhow!("Nothing has been rolled yet")),
+            },
+            _ => match line.split_once('=') {
+                Some((name, value)) if self.is_variable(name.trim()) => {
+                    self.assign(name.trim(), value.trim()).map(Some)
+                }
+                _ => self.roll(line).map(Some),
+            },
+        }
+    }
+
+    fn roll(&mut self, source: &str) -> Result<String> {
+        let evaluation = self.evaluate(source)?;
+        let text = evaluation.to_string();
+        self.last = Some((source.to_string(), evaluation));
+        Ok(text)
+    }
+
+    fn assign(&mut self, name: &str, source: &str) -> Result<String> {
+        let value = self.evaluate(source)?.total();
+        self.variables.insert(name.to_string(), value);
+        Ok(format!("{} = {}", name, value))
+    }
+
+    fn evaluate(&mut self, source: &str) -> Result<Evaluation> {
+        let expression = RollParser::expression_with(&self.registry, &self.substitute(source))?;
+        expression.validate()?;
+        expression.evaluate_with_limit(self.limit, &mut self.rng)
+    }
+
+    /// Replace each whole word naming a variable with its value.
+    fn substitute(&self, source: &str) -> String {
+        let mut text = String::new();
+        let mut rest = source;
+        while let Some(start) = rest.find(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            let (before, word) = rest.split_at(start);
+            let end = word
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(word.len());
+            text.push_str(before);
+            match self.variables.get(&word[..end]) {
+                Some(value) => text.push_str(&format!("({})", value)),
+                None => text.push_str(&word[..end]),
+            }
+            rest = &word[end..];
+        }
+        text.push_str(rest);
+        text
+    }
+
+    /// A variable is a word that could not otherwise be read as dice or part of them, such as
+    /// `str` but not `d6` or `b`.
+    fn is_variable(&self, name: &str) -> bool {
+        name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && RollParser::expression_with(&self.registry, name).is_err()
+            && !TOKENS.contains(&&name.to_lowercase()[..])
+            && !matches!(name, "last" | "again" | "exit" | "quit")
+    }
+}
+
+/// Read and roll expressions until the input ends, starting with `first` if given and keeping
+/// line history in `history` when there is somewhere to keep it.
+pub fn run(
+    seed: u64,
+    limit: usize,
+    registry: Registry,
+    history: Option<&Path>,
+    first: Option<&str>,
+) -> Result<()> {
+    let mut editor = Editor::<()>::new();
+    if let Some(history) = history {
+        // there is no history to load on the first run
+        let _ = editor.load_history(history);
+    }
+    let mut session = Session::new(seeded(seed), limit).with_registry(registry);
+
+    println!("rolling with seed {}, `exit` to leave", seed);
+    if let Some(line) = first {
+        editor.add_history_entry(line);
+        respond(&mut session, line);
+    }
+    loop {
+        let line = match editor.readline("roll> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) => break,
+            Err(error) => return Err(error.into()),
+        };
+        editor.add_history_entry(line.as_str());
+        if matches!(line.trim(), "exit" | "quit") {
+            break;
+        }
+        respond(&mut session, &line);
+    }
+    if let Some(history) = history {
+        editor.save_history(history)?;
+    }
+
+    Ok(())
+}
+
+fn respond<R: RngCore>(session: &mut Session<R>, line: &str) {
+    match session.line(line) {
+        Ok(Some(text)) => println!("{}", text),
+        Ok(None) => (),
+        Err(error) => match error.downcast_ref::<ParseError>() {
+            Some(error) => eprintln!("{}", error.render()),
+            None => eprintln!("{} {}", "error:".red().bold(), error),
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, rand_chacha::ChaCha8Rng, roll::DEFAULT_LIMIT};
+
+    fn session() -> Session<ChaCha8Rng> {
+        Session::new(seeded(1), DEFAULT_LIMIT)
+    }
+
+    #[test]
+    fn check_roll() -> Result<()> {
+        let mut session = session();
+
+        assert_eq!(session.line("")?, None);
+        assert!(session.line("2d6+1")?.is_some());
+        assert!(session.line("2d6+").is_err());
+        assert!(session.line("2d6k5").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_last_and_again() -> Result<()> {
+        let mut session = session();
+
+        assert!(session.line("last").is_err());
+        assert!(session.line("again").is_err());
+
+        let first = session.line("10d10")?;
+
+        assert_eq!(session.line("last")?, first);
+        assert_ne!(session.line("again")?, first);
+        assert_ne!(session.line("last")?, first);
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_variables() -> Result<()> {
+        let mut session = session();
+
+        assert_eq!(session.line("str = 3")?, Some("str = 3".to_string()));
+        assert_eq!(session.line("dex=str*2")?, Some("dex = 6".to_string()));
+        assert_eq!(session.line("str + dex")?, Some("3 + 6 => 9".to_string()));
+        assert_eq!(session.substitute("1d20+str"), "1d20+(3)");
+        assert_eq!(session.substitute("4d6k3 + dexterity"), "4d6k3 + dexterity");
+        assert!(session.line("1d20 + wis").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_registry() -> Result<()> {
+        let mut session =
+            session().with_registry(Registry::default().with_dice("hit", vec![0, 1, 2]));
+
+        assert!(session.line("3dhitk2")?.is_some());
+        assert!(session.line("3d[miss]").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_is_variable() {
+        let session = session();
+
+        assert!(session.is_variable("str"));
+        assert!(session.is_variable("hit_points"));
+        assert!(!session.is_variable("d6"));
+        assert!(!session.is_variable("2x"));
+        assert!(!session.is_variable("last"));
+        assert!(!session.is_variable(""));
+        assert!(!session.is_variable("b"));
+        assert!(!session.is_variable("SW"));
+        assert!(!session
+            .with_registry(Registry::default().with_dice("hit", vec![1]))
+            .is_variable("dhit"));
+    }
+
+    #[test]
+    fn check_token_variables() -> Result<()> {
+        let mut session = session();
+
+        assert!(session.line("b = 3").is_err());
+        assert!(session.line("sw = 1").is_err());
+        assert!(session.line("d = 2").is_err());
+        assert_eq!(session.substitute("1d100+b"), "1d100+b");
+        assert_eq!(session.substitute("sw(d8)"), "sw(d8)");
+        assert!(session.line("1d100+b")?.is_some());
+        assert!(session.line("sw(d8)")?.is_some());
+
+        Ok(())
+    }
+}
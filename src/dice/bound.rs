@@ -1,46 +1,197 @@
-use std::{
-    cmp::{Ord, Ordering, PartialOrd},
-    fmt::{self, Debug, Display},
-    ops::{Bound, RangeBounds},
+use {
+    anyhow::{anyhow, Error, Result},
+    serde::{Serialize, Serializer},
+    std::{
+        cmp::{Ord, Ordering, PartialOrd},
+        fmt::{self, Debug, Display},
+        ops::{Bound, RangeBounds, RangeInclusive},
+        str::FromStr,
+    },
 };
 
 #[derive(PartialEq, Eq, Clone, Copy)]
 pub struct Bounded {
-    start: Bound<i8>,
-    end: Bound<i8>,
+    start: Bound<i32>,
+    end: Bound<i32>,
 }
 
 impl Bounded {
-    pub fn from_range<T: RangeBounds<i8>>(range: T) -> Self {
+    pub fn from_range<T: RangeBounds<i32>>(range: T) -> Self {
         Self::new(range.start_bound().cloned(), range.end_bound().cloned())
     }
 
-    pub fn range_from(from: i8) -> Self {
+    pub fn range_from(from: i32) -> Self {
         Self::new(Bound::Included(from), Bound::Unbounded)
     }
 
-    pub fn range_to(to: i8) -> Self {
+    pub fn range_to(to: i32) -> Self {
         Self::new(Bound::Unbounded, Bound::Included(to))
     }
 
-    // pub fn range_between(from: i8, to: i8) -> Self {
-    //     Self::new(Bound::Included(from), Bound::Included(to))
-    // }
+    pub fn range_above(above: i32) -> Self {
+        Self::new(Bound::Excluded(above), Bound::Unbounded)
+    }
+
+    pub fn range_below(below: i32) -> Self {
+        Self::new(Bound::Unbounded, Bound::Excluded(below))
+    }
+
+    pub fn range_between(from: i32, to: i32) -> Self {
+        Self::new(Bound::Included(from), Bound::Included(to))
+    }
 
-    // pub fn range_of(value: i8) -> Self {
-    //     Self::new(Bound::Included(value), Bound::Included(value))
-    // }
+    pub fn range_of(value: i32) -> Self {
+        Self::new(Bound::Included(value), Bound::Included(value))
+    }
+
+    /// Parse a compare point, one of `=n`, `<n`, `>n`, `<=n`, `>=n`, `a:b` or a bare `n` read
+    /// by `bare`; an empty point is `None`, leaving the behaviour to its default.
+    pub fn parse_point(s: &str, bare: fn(i32) -> Bounded) -> Result<Option<Bounded>> {
+        Ok(Some(if s.is_empty() {
+            return Ok(None);
+        } else if let Some(value) = s.strip_prefix("<=") {
+            Self::range_to(value.parse()?)
+        } else if let Some(value) = s.strip_prefix(">=") {
+            Self::range_from(value.parse()?)
+        } else if let Some(value) = s.strip_prefix('<') {
+            Self::range_below(value.parse()?)
+        } else if let Some(value) = s.strip_prefix('>') {
+            Self::range_above(value.parse()?)
+        } else if let Some(value) = s.strip_prefix('=') {
+            Self::range_of(value.parse()?)
+        } else if let Some((from, to)) = s.split_once(':') {
+            Self::range_between(from.parse()?, to.parse()?)
+        } else {
+            bare(s.parse()?)
+        }))
+    }
 
-    fn new(start: Bound<i8>, end: Bound<i8>) -> Self {
+    /// Text of the range as a compare point, the reverse of `parse_point`.
+    pub fn point(&self, bare: fn(i32) -> Bounded) -> String {
+        let first = match self.start {
+            Bound::Included(value) => Some(value),
+            Bound::Excluded(value) => value.checked_add(1),
+            Bound::Unbounded => None,
+        };
+        let last = match self.end {
+            Bound::Included(value) => Some(value),
+            Bound::Excluded(value) => value.checked_sub(1),
+            Bound::Unbounded => None,
+        };
+        match (self.start, self.end) {
+            (Bound::Excluded(value), Bound::Unbounded) => format!(">{}", value),
+            (Bound::Unbounded, Bound::Excluded(value)) => format!("<{}", value),
+            _ => match (first, last) {
+                (Some(value), None) | (None, Some(value)) if value >= 0 && *self == bare(value) => {
+                    value.to_string()
+                }
+                (Some(value), None) => format!(">={}", value),
+                (None, Some(value)) => format!("<={}", value),
+                (Some(from), Some(to)) if from == to => format!("={}", from),
+                (Some(from), Some(to)) => format!("{}:{}", from, to),
+                (None, None) => format!(">={}", i32::MIN),
+            },
+        }
+    }
+
+    fn new(start: Bound<i32>, end: Bound<i32>) -> Self {
         Self { start, end }
     }
 
+    /// The overlap of both ranges, which may be empty.
+    pub fn intersection(&self, other: &Bounded) -> Bounded {
+        Self::new(
+            Self::later_start(self.start, other.start),
+            Self::earlier_end(self.end, other.end),
+        )
+    }
+
+    /// A range covering both ranges, if there is no gap between them.
+    pub fn union(&self, other: &Bounded) -> Option<Bounded> {
+        if self.is_empty() {
+            return Some(*other);
+        } else if other.is_empty() {
+            return Some(*self);
+        }
+        let (first, second) = if self <= other {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        if first.last().saturating_add(1) < second.first() {
+            None
+        } else {
+            Some(Self::new(
+                first.start,
+                match Self::cmp_end(&first.end, &second.end) {
+                    Ordering::Less => second.end,
+                    _ => first.end,
+                },
+            ))
+        }
+    }
+
+    /// The ranges holding every value outside this one.
+    pub fn complement(&self) -> Vec<Bounded> {
+        let mut ranges = Vec::new();
+        match self.start {
+            Bound::Included(value) => ranges.push(Self::range_below(value)),
+            Bound::Excluded(value) => ranges.push(Self::range_to(value)),
+            Bound::Unbounded => (),
+        }
+        match self.end {
+            Bound::Included(value) => ranges.push(Self::range_above(value)),
+            Bound::Excluded(value) => ranges.push(Self::range_from(value)),
+            Bound::Unbounded => (),
+        }
+        ranges
+    }
+
+    /// True if no whole number lies in the range.
+    pub fn is_empty(&self) -> bool {
+        self.first() > self.last()
+            || self.first() > i32::MAX as i64
+            || self.last() < i32::MIN as i64
+    }
+
+    /// True if none of `faces` lie in the range.
+    pub fn is_empty_within(&self, faces: &RangeInclusive<i32>) -> bool {
+        self.intersection(&Self::from_range(faces.clone()))
+            .is_empty()
+    }
+
+    /// True if every one of `faces` lies in the range.
+    pub fn covers(&self, faces: &RangeInclusive<i32>) -> bool {
+        self.complement()
+            .iter()
+            .all(|range| range.is_empty_within(faces))
+    }
+
+    /// Smallest whole number in the range.
+    fn first(&self) -> i64 {
+        match self.start {
+            Bound::Included(value) => value as i64,
+            Bound::Excluded(value) => value as i64 + 1,
+            Bound::Unbounded => i64::MIN,
+        }
+    }
+
+    /// Largest whole number in the range.
+    fn last(&self) -> i64 {
+        match self.end {
+            Bound::Included(value) => value as i64,
+            Bound::Excluded(value) => value as i64 - 1,
+            Bound::Unbounded => i64::MAX,
+        }
+    }
+
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             fmt,
             "{}..{}",
             match self.start {
-                Bound::Included(value) | Bound::Excluded(value) => value.to_string(),
+                Bound::Included(value) => value.to_string(),
+                Bound::Excluded(value) => format!("{}<", value),
                 Bound::Unbounded => String::from(""),
             },
             match self.end {
@@ -53,21 +204,49 @@ impl Bounded {
         Ok(())
     }
 
-    fn cmp_bound(this: &Bound<i8>, that: &Bound<i8>) -> Ordering {
-        match (this, that) {
-            (Bound::Unbounded, Bound::Unbounded) => Ordering::Equal,
-            (
-                Bound::Included(v1) | Bound::Excluded(v1),
-                Bound::Included(v2) | Bound::Excluded(v2),
-            ) => v1.cmp(&v2),
-            (Bound::Included(_) | Bound::Excluded(_), Bound::Unbounded) => Ordering::Less,
-            _ => Ordering::Greater,
+    /// Position of a start bound, where an excluded value starts just after the included one.
+    fn start_key(bound: &Bound<i32>) -> i64 {
+        match bound {
+            Bound::Included(value) => *value as i64 * 2,
+            Bound::Excluded(value) => *value as i64 * 2 + 1,
+            Bound::Unbounded => i64::MIN,
+        }
+    }
+
+    /// Position of an end bound, where an excluded value ends just before the included one.
+    fn end_key(bound: &Bound<i32>) -> i64 {
+        match bound {
+            Bound::Included(value) => *value as i64 * 2,
+            Bound::Excluded(value) => *value as i64 * 2 - 1,
+            Bound::Unbounded => i64::MAX,
+        }
+    }
+
+    fn cmp_start(this: &Bound<i32>, that: &Bound<i32>) -> Ordering {
+        Self::start_key(this).cmp(&Self::start_key(that))
+    }
+
+    fn cmp_end(this: &Bound<i32>, that: &Bound<i32>) -> Ordering {
+        Self::end_key(this).cmp(&Self::end_key(that))
+    }
+
+    fn later_start(this: Bound<i32>, that: Bound<i32>) -> Bound<i32> {
+        match Self::cmp_start(&this, &that) {
+            Ordering::Less => that,
+            _ => this,
+        }
+    }
+
+    fn earlier_end(this: Bound<i32>, that: Bound<i32>) -> Bound<i32> {
+        match Self::cmp_end(&this, &that) {
+            Ordering::Greater => that,
+            _ => this,
         }
     }
 }
 
-impl RangeBounds<i8> for Bounded {
-    fn start_bound(&self) -> Bound<&i8> {
+impl RangeBounds<i32> for Bounded {
+    fn start_bound(&self) -> Bound<&i32> {
         match self.start {
             Bound::Included(ref start) => Bound::Included(start),
             Bound::Excluded(ref start) => Bound::Excluded(start),
@@ -75,7 +254,7 @@ impl RangeBounds<i8> for Bounded {
         }
     }
 
-    fn end_bound(&self) -> Bound<&i8> {
+    fn end_bound(&self) -> Bound<&i32> {
         match self.end {
             Bound::Included(ref end) => Bound::Included(end),
             Bound::Excluded(ref end) => Bound::Excluded(end),
@@ -86,12 +265,8 @@ impl RangeBounds<i8> for Bounded {
 
 impl Ord for Bounded {
     fn cmp(&self, other: &Self) -> Ordering {
-        let cmp = Self::cmp_bound(&self.start, &other.start);
-        if cmp == Ordering::Equal {
-            Self::cmp_bound(&self.end, &other.end)
-        } else {
-            cmp
-        }
+        Self::cmp_start(&self.start, &other.start)
+            .then_with(|| Self::cmp_end(&self.end, &other.end))
     }
 }
 
@@ -109,7 +284,34 @@ impl Debug for Bounded {
 
 impl Display for Bounded {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        self.fmt(fmt) 
+        self.fmt(fmt)
+    }
+}
+
+impl FromStr for Bounded {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Bounded> {
+        let (start, end) = s
+            .split_once("..")
+            .ok_or_else(|| anyhow!("Unable to parse {} as a range", s))?;
+        let start = match start.strip_suffix('<') {
+            _ if start.is_empty() => Bound::Unbounded,
+            Some(value) => Bound::Excluded(value.parse()?),
+            None => Bound::Included(start.parse()?),
+        };
+        let end = match end.strip_prefix('=') {
+            _ if end.is_empty() => Bound::Unbounded,
+            Some(value) => Bound::Included(value.parse()?),
+            None => Bound::Excluded(end.parse()?),
+        };
+        Ok(Self::new(start, end))
+    }
+}
+
+impl Serialize for Bounded {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
     }
 }
 
@@ -122,19 +324,90 @@ mod test {
 
     #[test]
     fn check_compare() {
-        assert_eq!(Bounded::cmp_bound(&Unbounded, &Unbounded), Equal);
-        assert_eq!(Bounded::cmp_bound(&Unbounded, &Included(10)), Greater);
-        assert_eq!(Bounded::cmp_bound(&Unbounded, &Excluded(10)), Greater);
-        assert_eq!(Bounded::cmp_bound(&Included(10), &Unbounded), Less);
-        assert_eq!(Bounded::cmp_bound(&Included(10), &Included(10)), Equal);
-        assert_eq!(Bounded::cmp_bound(&Included(10), &Included(20)), Less);
-        assert_eq!(Bounded::cmp_bound(&Included(10), &Excluded(10)), Equal);
-        assert_eq!(Bounded::cmp_bound(&Included(10), &Excluded(20)), Less);
-        assert_eq!(Bounded::cmp_bound(&Excluded(10), &Unbounded), Less);
-        assert_eq!(Bounded::cmp_bound(&Excluded(10), &Included(10)), Equal);
-        assert_eq!(Bounded::cmp_bound(&Excluded(10), &Included(20)), Less);
-        assert_eq!(Bounded::cmp_bound(&Excluded(10), &Excluded(10)), Equal);
-        assert_eq!(Bounded::cmp_bound(&Excluded(10), &Excluded(20)), Less);
+        assert_eq!(Bounded::cmp_start(&Unbounded, &Unbounded), Equal);
+        assert_eq!(Bounded::cmp_start(&Unbounded, &Included(10)), Less);
+        assert_eq!(Bounded::cmp_start(&Included(10), &Included(10)), Equal);
+        assert_eq!(Bounded::cmp_start(&Included(10), &Included(20)), Less);
+        assert_eq!(Bounded::cmp_start(&Included(10), &Excluded(10)), Less);
+        assert_eq!(Bounded::cmp_start(&Excluded(10), &Included(11)), Less);
+        assert_eq!(Bounded::cmp_start(&Excluded(10), &Excluded(10)), Equal);
+
+        assert_eq!(Bounded::cmp_end(&Unbounded, &Unbounded), Equal);
+        assert_eq!(Bounded::cmp_end(&Unbounded, &Included(10)), Greater);
+        assert_eq!(Bounded::cmp_end(&Included(10), &Excluded(10)), Greater);
+        assert_eq!(Bounded::cmp_end(&Included(9), &Excluded(10)), Less);
+        assert_eq!(Bounded::cmp_end(&Excluded(10), &Excluded(20)), Less);
+
+        assert!(Bounded::range_from(5) < Bounded::range_above(5));
+        assert!(Bounded::range_below(5) < Bounded::range_to(5));
+        assert!(Bounded::range_to(5) < Bounded::range_from(1));
+    }
+
+    #[test]
+    fn check_contains() {
+        assert!(Bounded::range_above(5).contains(&6));
+        assert!(!Bounded::range_above(5).contains(&5));
+        assert!(Bounded::range_below(5).contains(&4));
+        assert!(!Bounded::range_below(5).contains(&5));
+        assert!(Bounded::new(Excluded(1), Excluded(3)).contains(&2));
+        assert!(!Bounded::new(Excluded(1), Excluded(3)).contains(&3));
+    }
+
+    #[test]
+    fn check_set_operations() {
+        let low = Bounded::range_to(3);
+        let high = Bounded::range_above(3);
+
+        assert_eq!(
+            low.intersection(&high),
+            Bounded::new(Excluded(3), Included(3))
+        );
+        assert!(low.intersection(&high).is_empty());
+        assert_eq!(
+            Bounded::range_between(2, 5).intersection(&Bounded::range_below(4)),
+            Bounded::new(Included(2), Excluded(4))
+        );
+
+        assert_eq!(low.union(&high), Some(Bounded::new(Unbounded, Unbounded)));
+        assert_eq!(
+            Bounded::range_between(1, 2).union(&Bounded::range_between(3, 4)),
+            Some(Bounded::range_between(1, 4))
+        );
+        assert_eq!(
+            Bounded::range_between(1, 2).union(&Bounded::range_between(4, 5)),
+            None
+        );
+        assert_eq!(
+            Bounded::range_of(9).union(&Bounded::range_between(3, 1)),
+            Some(Bounded::range_of(9))
+        );
+
+        assert_eq!(low.complement(), vec![high]);
+        assert_eq!(
+            Bounded::range_between(2, 5).complement(),
+            vec![Bounded::range_below(2), Bounded::range_above(5)]
+        );
+        assert!(Bounded::new(Unbounded, Unbounded).complement().is_empty());
+
+        assert!(Bounded::range_above(6).is_empty_within(&(1..=6)));
+        assert!(!Bounded::range_to(1).is_empty_within(&(1..=6)));
+        assert!(Bounded::range_below(7).covers(&(1..=6)));
+        assert!(!Bounded::range_below(6).covers(&(1..=6)));
+        assert!(Bounded::range_above(i32::MAX).is_empty());
+    }
+
+    #[test]
+    fn check_parse() -> Result<()> {
+        for text in [
+            "..", "10..", "10<..", "..=20", "..20", "10..=20", "10<..20", "-3..-1",
+        ] {
+            assert_eq!(text.parse::<Bounded>()?.to_string(), text);
+        }
+        assert_eq!("10<..".parse::<Bounded>()?, Bounded::range_above(10));
+        assert!("10".parse::<Bounded>().is_err());
+        assert!("a..b".parse::<Bounded>().is_err());
+
+        Ok(())
     }
 
     #[test]
@@ -146,7 +419,7 @@ mod test {
         );
         assert_eq!(
             format!("{:?}", Bounded::new(Excluded(10), Unbounded)),
-            "10.."
+            "10<.."
         );
         assert_eq!(
             format!("{:?}", Bounded::new(Unbounded, Included(20))),
@@ -170,7 +443,10 @@ mod test {
     fn check_display() {
         assert_eq!(format!("{}", Bounded::new(Unbounded, Unbounded)), "..");
         assert_eq!(format!("{}", Bounded::new(Included(10), Unbounded)), "10..");
-        assert_eq!(format!("{}", Bounded::new(Excluded(10), Unbounded)), "10..");
+        assert_eq!(
+            format!("{}", Bounded::new(Excluded(10), Unbounded)),
+            "10<.."
+        );
         assert_eq!(
             format!("{}", Bounded::new(Unbounded, Included(20))),
             "..=20"
@@ -185,4 +461,77 @@ mod test {
             "10..20"
         );
     }
+
+    #[test]
+    fn check_parse_point() -> Result<()> {
+        let bare = Bounded::range_from;
+
+        assert_eq!(Bounded::parse_point("", bare)?, None);
+        assert_eq!(
+            Bounded::parse_point("5", bare)?,
+            Some(Bounded::range_from(5))
+        );
+        assert_eq!(
+            Bounded::parse_point("5", Bounded::range_to)?,
+            Some(Bounded::range_to(5))
+        );
+        assert_eq!(
+            Bounded::parse_point("=5", bare)?,
+            Some(Bounded::range_of(5))
+        );
+        assert_eq!(
+            Bounded::parse_point("<5", bare)?,
+            Some(Bounded::range_below(5))
+        );
+        assert_eq!(
+            Bounded::parse_point(">5", bare)?,
+            Some(Bounded::range_above(5))
+        );
+        assert_eq!(
+            Bounded::parse_point("<=5", bare)?,
+            Some(Bounded::range_to(5))
+        );
+        assert_eq!(
+            Bounded::parse_point(">=-1", bare)?,
+            Some(Bounded::range_from(-1))
+        );
+        assert_eq!(
+            Bounded::parse_point("2:4", bare)?,
+            Some(Bounded::range_between(2, 4))
+        );
+        assert!(Bounded::parse_point(">", bare).is_err());
+        assert!(Bounded::parse_point("2:", bare).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_point() -> Result<()> {
+        for text in ["5", "=5", "<5", ">5", "<=5", "2:4", "=-1", ">=-1"] {
+            let point = Bounded::parse_point(text, Bounded::range_from)?;
+            assert_eq!(
+                point.map(|p| p.point(Bounded::range_from)),
+                Some(text.to_string())
+            );
+        }
+        assert_eq!(Bounded::range_from(5).point(Bounded::range_from), "5");
+        assert_eq!(Bounded::range_from(5).point(Bounded::range_to), ">=5");
+        assert_eq!(Bounded::range_to(5).point(Bounded::range_to), "5");
+        assert_eq!(
+            Bounded::new(Excluded(1), Excluded(5)).point(Bounded::range_to),
+            "2:4"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_serialize() -> serde_json::Result<()> {
+        assert_eq!(
+            serde_json::to_string(&Bounded::new(Included(10), Included(20)))?,
+            r#""10..=20""#
+        );
+
+        Ok(())
+    }
 }
@@ -1,9 +1,17 @@
 pub mod bound;
+pub mod narrative;
+pub mod registry;
 
 use rand::{distributions::Uniform, Rng, RngCore};
 use {
-    crate::dice::bound::Bounded,
+    crate::dice::{
+        bound::Bounded,
+        narrative::{Narrative, Symbolic},
+        registry::Registry,
+    },
     anyhow::{anyhow, Error, Result},
+    joinery::JoinableIterator,
+    serde::Serialize,
     std::{
         fmt::{self, Debug, Display},
         ops::RangeInclusive,
@@ -11,7 +19,7 @@ use {
     },
 };
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub enum Dice {
     D2,
     D3,
@@ -22,13 +30,21 @@ pub enum Dice {
     D12,
     D20,
     D100,
-    D00,
+    /// Rolled as a tens die and a units die, reading `00` and `0` as 100.
+    Percentile,
     Fate,
-    Other(i8, i8),
+    /// Dice of the given sides read as successive digits, so `d66` is two d6 as tens and units.
+    Digits(i32, u32),
+    Other(i32, i32),
+    Custom(Vec<i32>),
+    Named(String, Vec<i32>),
+    /// Faces numbered from 1 that each carry a set of symbols, such as the narrative dice.
+    Symbolic(Symbolic),
 }
 
 impl Dice {
-    pub fn faces(&self) -> RangeInclusive<i8> {
+    /// Lowest to highest face; the faces of a custom dice need not fill the range.
+    pub fn faces(&self) -> RangeInclusive<i32> {
         match self {
             Dice::D2 => 1..=2,
             Dice::D3 => 1..=3,
@@ -39,16 +55,41 @@ impl Dice {
             Dice::D12 => 1..=12,
             Dice::D20 => 1..=20,
             Dice::D100 => 1..=100,
-            Dice::D00 => 0..=00,
+            Dice::Percentile => 1..=100,
             Dice::Fate => -1..=1,
+            Dice::Digits(sides, count) => {
+                Self::concatenate(&vec![1; *count as usize])
+                    ..=Self::concatenate(&vec![*sides; *count as usize])
+            }
             Dice::Other(start, end) => *start..=*end,
+            Dice::Custom(faces) | Dice::Named(_, faces) => {
+                match (faces.iter().min(), faces.iter().max()) {
+                    (Some(start), Some(end)) => *start..=*end,
+                    _ => RangeInclusive::new(1, 0),
+                }
+            }
+            Dice::Symbolic(symbolic) => 1..=symbolic.faces().len() as i32,
+        }
+    }
+
+    /// Every side of the dice, with a face repeated once for each side showing it.
+    pub fn sides(&self) -> Vec<i32> {
+        match self {
+            Dice::Custom(faces) | Dice::Named(_, faces) => faces.clone(),
+            Dice::Digits(sides, count) => (0..*count).fold(vec![0], |values, _| {
+                values
+                    .iter()
+                    .flat_map(|value| (1..=*sides).map(move |digit| value * 10 + digit))
+                    .collect()
+            }),
+            _ => self.faces().collect(),
         }
     }
 
-    pub(crate) fn critical(&self) -> Option<i8> {
+    pub(crate) fn critical(&self) -> Option<i32> {
         match self {
-            Dice::D100 | Dice::D00 => Some(5),
-            Dice::Fate => None,
+            Dice::D100 | Dice::Percentile => Some(5),
+            Dice::Fate | Dice::Symbolic(_) => None,
             _ => Some(1),
         }
     }
@@ -65,13 +106,13 @@ impl Dice {
     pub(crate) fn end(&self) -> Option<Bounded> {
         if let Some(crit) = self.critical() {
             let end = *self.faces().end();
-            Some(Bounded::from_range(end + 1 - crit..))
+            Some(Bounded::from_range(end - (crit - 1)..))
         } else {
             None
         }
     }
 
-    pub(crate) fn text(&self, value: i8) -> String {
+    pub(crate) fn text(&self, value: i32) -> String {
         let v = self.value(value);
         match self {
             Dice::Fate => match v {
@@ -79,6 +120,10 @@ impl Dice {
                 1 => String::from("+"),
                 _ => String::from("0"),
             },
+            Dice::Symbolic(symbolic) => symbolic.text(v),
+            Dice::Digits(..) => v.to_string(),
+            Dice::D100 | Dice::Percentile if v == 100 => String::from("100"),
+            Dice::D100 | Dice::Percentile => format!("{:02}", v),
             _ => {
                 if self.faces().end() < &10 {
                     format!("{}", v)
@@ -91,29 +136,62 @@ impl Dice {
         }
     }
 
-    fn value(&self, value: i8) -> i8 {
+    fn value(&self, value: i32) -> i32 {
         value
     }
 
-    pub fn roll(&self, rng: &mut dyn RngCore) -> i8 {
-        rng.gen_range(self.faces())
+    /// Value read from `digits` rolled in order, the first as the most significant.
+    pub fn concatenate(digits: &[i32]) -> i32 {
+        digits.iter().fold(0, |value, digit| value * 10 + digit)
     }
 
-    fn parse_other(s: &str) -> Result<Dice> {
-        let mut parts = s.split(':');
-        match (parts.next(), parts.next()) {
-            (Some(end), None) => Ok(Dice::Other(1, end.parse()?)),
-            (Some(""), Some(end)) => Ok(Dice::Other(1, end.parse()?)),
-            (Some(start), Some(end)) => Ok(Dice::Other(start.parse()?, end.parse()?)),
-            _ => Err(anyhow!("Unable to parse {}", s)),
+    /// Percentile dice, whose faces can be read as a tens die and a units die.
+    pub(crate) fn has_tens(&self) -> bool {
+        matches!(self, Dice::D100 | Dice::Percentile)
+    }
+
+    /// Percentile value read from a tens die and a units die, each showing 0 to 9.
+    pub fn percentile(tens: i32, units: i32) -> i32 {
+        match tens * 10 + units {
+            0 => 100,
+            value => value,
         }
     }
-}
 
-impl FromStr for Dice {
-    type Err = Error;
+    /// Roll the tens die of a percentile value again, keeping its units die.
+    pub(crate) fn reroll_tens(value: i32, rng: &mut dyn RngCore) -> i32 {
+        Self::percentile(rng.gen_range(0..10), value % 10)
+    }
 
-    fn from_str(s: &str) -> Result<Dice> {
+    /// Fails rather than panicking for dice without faces, such as `d{}` or `d5:2`.
+    pub fn roll(&self, rng: &mut dyn RngCore) -> Result<i32> {
+        self.check()?;
+        Ok(match self {
+            Dice::Percentile => Self::percentile(rng.gen_range(0..10), rng.gen_range(0..10)),
+            Dice::Digits(sides, count) => Self::concatenate(
+                &(0..*count)
+                    .map(|_| rng.gen_range(1..=*sides))
+                    .collect::<Vec<_>>(),
+            ),
+            Dice::Custom(faces) | Dice::Named(_, faces) => {
+                faces[rng.gen_range(0..faces.len() as u32) as usize]
+            }
+            _ => rng.gen_range(self.faces()),
+        })
+    }
+
+    fn check(&self) -> Result<()> {
+        match self {
+            Dice::Custom(faces) | Dice::Named(_, faces) if faces.is_empty() => {
+                Err(anyhow!("{} has no faces to roll", self))
+            }
+            _ if self.faces().is_empty() => Err(anyhow!("{} has no faces to roll", self)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Parse the dice written after the `d`, reading any named dice from `registry`.
+    pub fn parse_with(registry: &Registry, s: &str) -> Result<Dice> {
         match s {
             "2" => Ok(Dice::D2),
             "3" => Ok(Dice::D3),
@@ -124,13 +202,75 @@ impl FromStr for Dice {
             "12" => Ok(Dice::D12),
             "20" => Ok(Dice::D20),
             "100" => Ok(Dice::D100),
-            "00" => Ok(Dice::D00),
-            "%" => Ok(Dice::D00),
-            "Fate" => Ok(Dice::Fate),
-            "F" => Ok(Dice::Fate),
-            _ => Self::parse_other(s),
+            "00" => Ok(Dice::Percentile),
+            "%" => Ok(Dice::Percentile),
+            "Fate" | "fate" => Ok(Dice::Fate),
+            "F" | "f" => Ok(Dice::Fate),
+            _ => match s.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                Some(faces) => Self::parse_custom(faces),
+                None => match s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+                    Some(name) => Self::parse_named(registry, name),
+                    None => match s.parse::<Narrative>() {
+                        Ok(narrative) => Ok(Dice::from(narrative)),
+                        Err(_) if s.starts_with(|c: char| c.is_ascii_lowercase()) => {
+                            Self::parse_named(registry, s)
+                        }
+                        Err(_) => Self::parse_other(s),
+                    },
+                },
+            },
+        }
+    }
+
+    fn parse_named(registry: &Registry, s: &str) -> Result<Dice> {
+        registry.dice(s).ok_or_else(|| {
+            anyhow!(
+                "Unknown dice `{}`, define it as [dice.{}] with its faces or symbols in the dice config",
+                s,
+                s
+            )
+        })
+    }
+
+    /// The digit dice of the tables that use them, `66`, `666` and `88`; any other number is
+    /// a flat dice, so `d44` still rolls 1 to 44.
+    fn parse_digits(s: &str) -> Option<Dice> {
+        match s {
+            "66" => Some(Dice::Digits(6, 2)),
+            "666" => Some(Dice::Digits(6, 3)),
+            "88" => Some(Dice::Digits(8, 2)),
+            _ => None,
         }
     }
+
+    fn parse_other(s: &str) -> Result<Dice> {
+        if let Some(dice) = Self::parse_digits(s) {
+            return Ok(dice);
+        }
+        let mut parts = s.split(':');
+        match (parts.next(), parts.next()) {
+            (Some(end), None) => Ok(Dice::Other(1, end.parse()?)),
+            (Some(""), Some(end)) => Ok(Dice::Other(1, end.parse()?)),
+            (Some(start), Some(end)) => Ok(Dice::Other(start.parse()?, end.parse()?)),
+            _ => Err(anyhow!("Unable to parse {}", s)),
+        }
+    }
+
+    fn parse_custom(s: &str) -> Result<Dice> {
+        let faces = s
+            .split(',')
+            .map(|face| face.trim().parse())
+            .collect::<std::result::Result<Vec<i32>, _>>()?;
+        Ok(Dice::Custom(faces))
+    }
+}
+
+impl FromStr for Dice {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Dice> {
+        Self::parse_with(&Registry::default(), s)
+    }
 }
 
 impl Display for Dice {
@@ -138,6 +278,21 @@ impl Display for Dice {
         match self {
             Dice::Other(1, end) => write!(f, "D{}", end),
             Dice::Other(start, end) => write!(f, "D{}:{}", start, end),
+            Dice::Custom(faces) => write!(f, "d{{{}}}", faces.iter().join_with(',')),
+            Dice::Digits(sides, count) => {
+                write!(f, "d{}", sides.to_string().repeat(*count as usize))
+            }
+            Dice::Named(name, _) => write!(f, "d[{}]", name),
+            // only the built-in narrative dice are written bare, as `dAbility`
+            Dice::Symbolic(symbolic)
+                if symbolic
+                    .name()
+                    .parse::<Narrative>()
+                    .is_ok_and(|narrative| narrative.to_string() == symbolic.name()) =>
+            {
+                write!(f, "d{}", symbolic)
+            }
+            Dice::Symbolic(symbolic) => write!(f, "d[{}]", symbolic),
             _ => write!(
                 f,
                 "{}",
@@ -150,7 +305,7 @@ impl Display for Dice {
                     Dice::D10 => "d10",
                     Dice::D12 => "d12",
                     Dice::D20 => "d20",
-                    Dice::D00 => "d00",
+                    Dice::Percentile => "d%",
                     Dice::D100 => "d100",
                     Dice::Fate => "Fate",
                     _ => "",
@@ -160,25 +315,55 @@ impl Display for Dice {
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct Die {
     dice: Dice,
-    count: u8,
+    count: u32,
 }
 
 impl Die {
-    pub(crate) fn new(dice: Dice, count: u8) -> Self {
+    pub fn new(dice: Dice, count: u32) -> Self {
         Self { dice, count }
     }
 
-    pub(crate) fn dice(&self) -> &Dice {
+    pub fn dice(&self) -> &Dice {
         &self.dice
     }
 
-    pub fn roll(&self, rng: &mut dyn RngCore) -> Vec<i8> {
-        let faces = self.dice.faces();
-        let range = Uniform::new_inclusive(faces.start(), faces.end());
-        rng.sample_iter(range).take(self.count as usize).collect()
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn roll(&self, rng: &mut dyn RngCore) -> Result<Vec<i32>> {
+        self.dice.check()?;
+        Ok(match &self.dice {
+            Dice::Custom(faces) | Dice::Named(_, faces) => rng
+                .sample_iter(Uniform::new(0, faces.len() as u32))
+                .take(self.count as usize)
+                .map(|index| faces[index as usize])
+                .collect(),
+            Dice::Percentile | Dice::Digits(..) => (0..self.count)
+                .map(|_| self.dice.roll(rng))
+                .collect::<Result<_>>()?,
+            dice => {
+                let faces = dice.faces();
+                let range = Uniform::new_inclusive(faces.start(), faces.end());
+                rng.sample_iter(range).take(self.count as usize).collect()
+            }
+        })
+    }
+}
+
+impl Die {
+    /// Parse a count and dice such as `3dhit`, reading any named dice from `registry`.
+    pub fn parse_with(registry: &Registry, s: &str) -> Result<Die> {
+        let (count, dice) = s.split_once(['d', 'D']).unwrap_or((s, ""));
+
+        let count: u32 = match count {
+            "" => 1,
+            count => count.parse()?,
+        };
+        Ok(Die::new(Dice::parse_with(registry, dice)?, count))
     }
 }
 
@@ -186,11 +371,7 @@ impl FromStr for Die {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Die> {
-        let mut parts = s.split('d');
-
-        let count: u8 = parts.next().unwrap_or("1").parse().unwrap_or(1);
-        let dice: Dice = parts.next().unwrap_or("").parse()?;
-        Ok(Die::new(dice, count))
+        Self::parse_with(&Registry::default(), s)
     }
 }
 
@@ -215,8 +396,8 @@ mod test {
         assert_eq!("12".parse::<Dice>()?, Dice::D12);
         assert_eq!("20".parse::<Dice>()?, Dice::D20);
         assert_eq!("100".parse::<Dice>()?, Dice::D100);
-        assert_eq!("00".parse::<Dice>()?, Dice::D00);
-        assert_eq!("%".parse::<Dice>()?, Dice::D00);
+        assert_eq!("00".parse::<Dice>()?, Dice::Percentile);
+        assert_eq!("%".parse::<Dice>()?, Dice::Percentile);
         assert_eq!("F".parse::<Dice>()?, Dice::Fate);
         assert_eq!("Fate".parse::<Dice>()?, Dice::Fate);
 
@@ -224,7 +405,36 @@ mod test {
         assert!(matches!(":7".parse::<Dice>()?, Dice::Other(1, 7)));
         assert!(matches!("2:7".parse::<Dice>()?, Dice::Other(2, 7)));
 
-        assert!(matches!("S".parse::<Dice>(), Err(_)));
+        assert!(matches!("1000".parse::<Dice>()?, Dice::Other(1, 1000)));
+
+        assert_eq!("66".parse::<Dice>()?, Dice::Digits(6, 2));
+        assert_eq!("666".parse::<Dice>()?, Dice::Digits(6, 3));
+        assert_eq!("88".parse::<Dice>()?, Dice::Digits(8, 2));
+        assert_eq!("11".parse::<Dice>()?, Dice::Other(1, 11));
+        assert_eq!("67".parse::<Dice>()?, Dice::Other(1, 67));
+        assert_eq!("66666".parse::<Dice>()?, Dice::Other(1, 66666));
+        assert_eq!("44".parse::<Dice>()?, Dice::Other(1, 44));
+        assert_eq!("99".parse::<Dice>()?, Dice::Other(1, 99));
+        assert_eq!("888".parse::<Dice>()?, Dice::Other(1, 888));
+
+        assert_eq!(
+            "{1,1,2,3,5,8}".parse::<Dice>()?,
+            Dice::Custom(vec![1, 1, 2, 3, 5, 8])
+        );
+        assert_eq!(
+            "{-2, 0, 0, 2}".parse::<Dice>()?,
+            Dice::Custom(vec![-2, 0, 0, 2])
+        );
+
+        assert_eq!("Ability".parse::<Dice>()?, Dice::from(Narrative::Ability));
+        assert_eq!(
+            "challenge".parse::<Dice>()?,
+            Dice::from(Narrative::Challenge)
+        );
+
+        assert!("S".parse::<Dice>().is_err());
+        assert!("{1,x}".parse::<Dice>().is_err());
+        assert!("99999999999".parse::<Dice>().is_err());
 
         Ok(())
     }
@@ -250,34 +460,183 @@ mod test {
         assert_eq!(Dice::Fate.critical(), None);
     }
 
+    #[test]
+    fn check_percentile_dice_values() {
+        assert_eq!(Dice::Percentile.faces(), 1..=100);
+        assert_eq!(Dice::Percentile.text(5), "05");
+        assert_eq!(Dice::Percentile.text(90), "90");
+        assert_eq!(Dice::Percentile.text(100), "100");
+        assert_eq!(Dice::Percentile.to_string(), "d%");
+        assert_eq!(Dice::D100.text(7), "07");
+
+        assert_eq!(Dice::percentile(3, 7), 37);
+        assert_eq!(Dice::percentile(0, 4), 4);
+        assert_eq!(Dice::percentile(0, 0), 100);
+        assert_eq!(Dice::percentile(9, 0), 90);
+    }
+
+    #[test]
+    fn check_digits_dice_values() {
+        let d66 = Dice::Digits(6, 2);
+
+        assert_eq!(d66.faces(), 11..=66);
+        assert_eq!(d66.sides().len(), 36);
+        assert_eq!(&d66.sides()[..7], &[11, 12, 13, 14, 15, 16, 21]);
+        assert_eq!(d66.text(34), "34");
+        assert_eq!(d66.to_string(), "d66");
+        assert_eq!(d66.start(), Some(Bounded::from_range(..12)));
+        assert_eq!(d66.end(), Some(Bounded::from_range(66..)));
+
+        let d666 = Dice::Digits(6, 3);
+
+        assert_eq!(d666.faces(), 111..=666);
+        assert_eq!(d666.sides().len(), 216);
+        assert_eq!(d666.to_string(), "d666");
+        assert_eq!(Dice::concatenate(&[3, 1, 4]), 314);
+    }
+
+    #[test]
+    fn check_custom_dice_values() {
+        let fibonacci = Dice::Custom(vec![1, 1, 2, 3, 5, 8]);
+
+        assert_eq!(fibonacci.faces(), 1..=8);
+        assert_eq!(fibonacci.sides(), vec![1, 1, 2, 3, 5, 8]);
+        assert_eq!(fibonacci.text(5), "5");
+        assert_eq!(fibonacci.start(), Some(Bounded::from_range(..2)));
+        assert_eq!(fibonacci.end(), Some(Bounded::from_range(8..)));
+        assert_eq!(fibonacci.to_string(), "d{1,1,2,3,5,8}");
+
+        let offset = Dice::Custom(vec![-2, 0, 0, 2]);
+
+        assert_eq!(offset.faces(), -2..=2);
+        assert_eq!(offset.start(), Some(Bounded::from_range(..-1)));
+        assert_eq!(offset.to_string(), "d{-2,0,0,2}");
+
+        assert!(Dice::Custom(Vec::new()).faces().is_empty());
+    }
+
+    #[test]
+    fn check_narrative_dice_values() {
+        colored::control::set_override(false);
+        let proficiency = Dice::from(Narrative::Proficiency);
+
+        assert_eq!(proficiency.faces(), 1..=12);
+        assert_eq!(proficiency.text(1), "-");
+        assert_eq!(proficiency.text(7), "sa");
+        assert_eq!(proficiency.critical(), None);
+        assert_eq!(proficiency.to_string(), "dProficiency");
+    }
+
     #[test]
     fn check_die_parsing() -> Result<()> {
         assert_eq!("d10".parse::<Die>()?, Die::new(Dice::D10, 1));
         assert_eq!("1d10".parse::<Die>()?, Die::new(Dice::D10, 1));
         assert_eq!("2d10".parse::<Die>()?, Die::new(Dice::D10, 2));
 
-        assert!(matches!("2d".parse::<Die>(), Err(_)));
-        assert!(matches!("d".parse::<Die>(), Err(_)));
-        assert!(matches!("2".parse::<Die>(), Err(_)));
+        assert!("2d".parse::<Die>().is_err());
+        assert!("d".parse::<Die>().is_err());
+        assert_eq!("200d20".parse::<Die>()?, Die::new(Dice::D20, 200));
+        assert_eq!(
+            "300d1000".parse::<Die>()?,
+            Die::new(Dice::Other(1, 1000), 300)
+        );
+
+        assert_eq!(
+            "3d{-2,0,0,2}".parse::<Die>()?,
+            Die::new(Dice::Custom(vec![-2, 0, 0, 2]), 3)
+        );
+
+        assert_eq!(
+            "2dDifficulty".parse::<Die>()?,
+            Die::new(Dice::from(Narrative::Difficulty), 2)
+        );
+        assert_eq!("2D6".parse::<Die>()?, Die::new(Dice::D6, 2));
+
+        assert!("2".parse::<Die>().is_err());
+        assert!("99999999999d6".parse::<Die>().is_err());
 
         Ok(())
     }
 
     #[test]
-    fn check_dice_roll() {
+    fn check_dice_roll() -> Result<()> {
         let mut rng = rng(Dice::D10, 0);
 
-        assert_eq!(Dice::D10.roll(&mut rng), 1);
-        assert_eq!(Dice::D10.roll(&mut rng), 2);
-        assert_eq!(Dice::D10.roll(&mut rng), 3);
-        assert_eq!(Dice::D10.roll(&mut rng), 4);
-        assert_eq!(Dice::D10.roll(&mut rng), 5);
+        assert_eq!(Dice::D10.roll(&mut rng)?, 1);
+        assert_eq!(Dice::D10.roll(&mut rng)?, 2);
+        assert_eq!(Dice::D10.roll(&mut rng)?, 3);
+        assert_eq!(Dice::D10.roll(&mut rng)?, 4);
+        assert_eq!(Dice::D10.roll(&mut rng)?, 5);
+
+        Ok(())
     }
 
     #[test]
-    fn check_die_rolls() {
+    fn check_custom_dice_roll() -> Result<()> {
+        let dice = Dice::Custom(vec![1, 1, 2, 3, 5, 8]);
+        let mut rng = rng(dice.clone(), 1);
+
+        assert_eq!(dice.roll(&mut rng)?, 1);
+        assert_eq!(dice.roll(&mut rng)?, 2);
+        assert_eq!(dice.roll(&mut rng)?, 3);
+        assert_eq!(Die::new(dice, 4).roll(&mut rng)?, vec![5, 8, 1, 1]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_percentile_dice_roll() -> Result<()> {
+        let mut rng = seq_rng(Dice::D10, [3, 7, 0, 0, 0, 9, 6].into_iter());
+
+        assert_eq!(Dice::Percentile.roll(&mut rng)?, 37);
+        assert_eq!(Dice::Percentile.roll(&mut rng)?, 100);
+        assert_eq!(Dice::Percentile.roll(&mut rng)?, 9);
+        assert_eq!(Dice::reroll_tens(9, &mut rng), 69);
+        assert_eq!(Die::new(Dice::Percentile, 2).roll(&mut rng)?, vec![37, 100]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_digits_dice_roll() -> Result<()> {
+        let d66 = Dice::Digits(6, 2);
+        let mut rng = seq_rng(Dice::D6, [2, 3, 5, 0].into_iter());
+
+        assert_eq!(d66.roll(&mut rng)?, 34);
+        assert_eq!(d66.roll(&mut rng)?, 61);
+        assert_eq!(Die::new(d66, 2).roll(&mut rng)?, vec![34, 61]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_empty_dice_roll() {
+        let mut rng = rng(Dice::D6, 0);
+
+        assert!(Dice::Custom(Vec::new()).roll(&mut rng).is_err());
+        assert!(Dice::Other(5, 2).roll(&mut rng).is_err());
+        assert!(Die::new(Dice::Named("none".to_string(), Vec::new()), 2)
+            .roll(&mut rng)
+            .is_err());
+    }
+
+    #[test]
+    fn check_die_rolls() -> Result<()> {
         let mut rng = rng(Dice::D100, 0);
 
-        assert_eq!(Die::new(Dice::D100, 5).roll(&mut rng), vec![1, 2, 3, 4, 5]);
+        assert_eq!(Die::new(Dice::D100, 5).roll(&mut rng)?, vec![1, 2, 3, 4, 5]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_large_dice_rolls() -> Result<()> {
+        let mut rng = rng(Dice::Other(1, 1000), 999);
+
+        assert_eq!(Dice::Other(1, 1000).roll(&mut rng)?, 1000);
+        assert_eq!(Dice::Other(1, 1000).text(1000), "1000");
+        assert_eq!(Die::new(Dice::D20, 200).roll(&mut rng)?.len(), 200);
+
+        Ok(())
     }
 }
@@ -0,0 +1,507 @@
+use {
+    crate::dice::Dice,
+    anyhow::{anyhow, Error, Result},
+    colored::Colorize,
+    serde::Serialize,
+    std::{
+        fmt::{self, Display},
+        iter::Sum,
+        ops::Add,
+        str::FromStr,
+    },
+};
+
+/// A symbol printed on a face of a narrative dice, shown by its letter in the narrative dice
+/// font so that `x` is a triumph and `y` a despair.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub enum Symbol {
+    Success,
+    Advantage,
+    Triumph,
+    Failure,
+    Threat,
+    Despair,
+}
+
+use Symbol::*;
+
+impl Symbol {
+    fn positive(&self) -> bool {
+        matches!(self, Success | Advantage | Triumph)
+    }
+
+    /// Symbols on a face written by their letters, such as `sa`, with `-` or nothing for a blank.
+    pub fn face(s: &str) -> Result<Vec<Symbol>> {
+        match s {
+            "" | "-" => Ok(Vec::new()),
+            _ => s.chars().map(Symbol::try_from).collect(),
+        }
+    }
+}
+
+impl TryFrom<char> for Symbol {
+    type Error = Error;
+
+    fn try_from(c: char) -> Result<Symbol> {
+        match c {
+            's' => Ok(Success),
+            'a' => Ok(Advantage),
+            'x' => Ok(Triumph),
+            'f' => Ok(Failure),
+            't' => Ok(Threat),
+            'y' => Ok(Despair),
+            _ => Err(anyhow!("Unknown symbol `{}`, use s, a, x, f, t or y", c)),
+        }
+    }
+}
+
+impl Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Success => "s",
+                Advantage => "a",
+                Triumph => "x",
+                Failure => "f",
+                Threat => "t",
+                Despair => "y",
+            }
+        )
+    }
+}
+
+const BOOST: &[&[Symbol]] = &[
+    &[],
+    &[],
+    &[Success],
+    &[Success, Advantage],
+    &[Advantage, Advantage],
+    &[Advantage],
+];
+
+const SETBACK: &[&[Symbol]] = &[&[], &[], &[Failure], &[Failure], &[Threat], &[Threat]];
+
+const ABILITY: &[&[Symbol]] = &[
+    &[],
+    &[Success],
+    &[Success],
+    &[Success, Success],
+    &[Advantage],
+    &[Advantage],
+    &[Success, Advantage],
+    &[Advantage, Advantage],
+];
+
+const DIFFICULTY: &[&[Symbol]] = &[
+    &[],
+    &[Failure],
+    &[Failure, Failure],
+    &[Threat],
+    &[Threat],
+    &[Threat],
+    &[Threat, Threat],
+    &[Failure, Threat],
+];
+
+const PROFICIENCY: &[&[Symbol]] = &[
+    &[],
+    &[Success],
+    &[Success],
+    &[Success, Success],
+    &[Success, Success],
+    &[Advantage],
+    &[Success, Advantage],
+    &[Success, Advantage],
+    &[Success, Advantage],
+    &[Advantage, Advantage],
+    &[Advantage, Advantage],
+    &[Triumph],
+];
+
+const CHALLENGE: &[&[Symbol]] = &[
+    &[],
+    &[Failure],
+    &[Failure],
+    &[Failure, Failure],
+    &[Failure, Failure],
+    &[Threat],
+    &[Threat],
+    &[Failure, Threat],
+    &[Failure, Threat],
+    &[Threat, Threat],
+    &[Threat, Threat],
+    &[Despair],
+];
+
+/// Dice rolled as faces numbered from 1 that each carry a set of symbols. The narrative dice
+/// are a built-in set, and others can be defined with their faces in the dice config.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Symbolic {
+    name: String,
+    faces: Vec<Vec<Symbol>>,
+}
+
+impl Symbolic {
+    pub fn new(name: &str, faces: Vec<Vec<Symbol>>) -> Self {
+        Self {
+            name: name.to_string(),
+            faces,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn faces(&self) -> &[Vec<Symbol>] {
+        &self.faces
+    }
+
+    /// Symbols on the face numbered `value`, none for a blank or unknown face.
+    pub fn face(&self, value: i32) -> &[Symbol] {
+        usize::try_from(value - 1)
+            .ok()
+            .and_then(|index| self.faces.get(index))
+            .map(|face| &face[..])
+            .unwrap_or_default()
+    }
+
+    pub fn symbols(&self, value: i32) -> Symbols {
+        Symbols::of(self.face(value))
+    }
+
+    pub(crate) fn text(&self, value: i32) -> String {
+        let face = self.face(value);
+        if face.is_empty() {
+            return String::from("-");
+        }
+        face.iter()
+            .map(|symbol| {
+                let text = symbol.to_string();
+                let text = if symbol.positive() {
+                    text.green()
+                } else {
+                    text.red()
+                };
+                match symbol {
+                    Triumph | Despair => text.bold().to_string(),
+                    _ => text.to_string(),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Display for Symbolic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// The built-in dice of the Genesys and Star Wars narrative dice system.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub enum Narrative {
+    Boost,
+    Setback,
+    Ability,
+    Difficulty,
+    Proficiency,
+    Challenge,
+}
+
+impl Narrative {
+    fn faces(&self) -> &'static [&'static [Symbol]] {
+        match self {
+            Self::Boost => BOOST,
+            Self::Setback => SETBACK,
+            Self::Ability => ABILITY,
+            Self::Difficulty => DIFFICULTY,
+            Self::Proficiency => PROFICIENCY,
+            Self::Challenge => CHALLENGE,
+        }
+    }
+
+    pub fn symbolic(&self) -> Symbolic {
+        Symbolic::new(
+            &self.to_string(),
+            self.faces().iter().map(|face| face.to_vec()).collect(),
+        )
+    }
+}
+
+impl From<Narrative> for Dice {
+    fn from(narrative: Narrative) -> Dice {
+        Dice::Symbolic(narrative.symbolic())
+    }
+}
+
+impl FromStr for Narrative {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Narrative> {
+        match &s.to_lowercase()[..] {
+            "boost" => Ok(Self::Boost),
+            "setback" => Ok(Self::Setback),
+            "ability" => Ok(Self::Ability),
+            "difficulty" => Ok(Self::Difficulty),
+            "proficiency" => Ok(Self::Proficiency),
+            "challenge" => Ok(Self::Challenge),
+            _ => Err(anyhow!("Unable to parse {} as a narrative dice", s)),
+        }
+    }
+}
+
+impl Display for Narrative {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Boost => "Boost",
+                Self::Setback => "Setback",
+                Self::Ability => "Ability",
+                Self::Difficulty => "Difficulty",
+                Self::Proficiency => "Proficiency",
+                Self::Challenge => "Challenge",
+            }
+        )
+    }
+}
+
+/// Count of each symbol rolled; a triumph also counts as a success and a despair as a failure.
+#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone, Copy, Default, Serialize)]
+pub struct Symbols {
+    success: u32,
+    failure: u32,
+    advantage: u32,
+    threat: u32,
+    triumph: u32,
+    despair: u32,
+}
+
+impl Symbols {
+    pub fn of(symbols: &[Symbol]) -> Self {
+        let mut result = Self::default();
+        for symbol in symbols {
+            match symbol {
+                Success => result.success += 1,
+                Advantage => result.advantage += 1,
+                Triumph => {
+                    result.triumph += 1;
+                    result.success += 1;
+                }
+                Failure => result.failure += 1,
+                Threat => result.threat += 1,
+                Despair => {
+                    result.despair += 1;
+                    result.failure += 1;
+                }
+            }
+        }
+        result
+    }
+
+    /// Cancel each success against a failure and each advantage against a threat, leaving
+    /// triumphs and despairs in place.
+    pub fn cancel(self) -> Self {
+        let success = self.success.min(self.failure);
+        let advantage = self.advantage.min(self.threat);
+        Self {
+            success: self.success - success,
+            failure: self.failure - success,
+            advantage: self.advantage - advantage,
+            threat: self.threat - advantage,
+            ..self
+        }
+    }
+
+    /// Successes less failures, negative when the failures win.
+    pub fn net(&self) -> i32 {
+        self.success as i32 - self.failure as i32
+    }
+
+    pub fn success(&self) -> u32 {
+        self.success
+    }
+
+    pub fn failure(&self) -> u32 {
+        self.failure
+    }
+
+    pub fn advantage(&self) -> u32 {
+        self.advantage
+    }
+
+    pub fn threat(&self) -> u32 {
+        self.threat
+    }
+
+    pub fn triumph(&self) -> u32 {
+        self.triumph
+    }
+
+    pub fn despair(&self) -> u32 {
+        self.despair
+    }
+}
+
+impl Add for Symbols {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            success: self.success + other.success,
+            failure: self.failure + other.failure,
+            advantage: self.advantage + other.advantage,
+            threat: self.threat + other.threat,
+            triumph: self.triumph + other.triumph,
+            despair: self.despair + other.despair,
+        }
+    }
+}
+
+impl Sum for Symbols {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+impl Display for Symbols {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts = Vec::new();
+        match (self.success, self.failure) {
+            (0, 0) => parts.push(String::from("0 successes")),
+            (success, failure) => {
+                if success > 0 {
+                    parts.push(plural(success, "success", "successes"));
+                }
+                if failure > 0 {
+                    parts.push(plural(failure, "failure", "failures"));
+                }
+            }
+        }
+        for (count, name) in [
+            (self.advantage, "advantage"),
+            (self.threat, "threat"),
+            (self.triumph, "triumph"),
+            (self.despair, "despair"),
+        ] {
+            if count > 0 {
+                parts.push(format!("{} {}", count, name));
+            }
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+fn plural(count: u32, one: &str, many: &str) -> String {
+    format!("{} {}", count, if count == 1 { one } else { many })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn check_faces() {
+        assert_eq!(Narrative::Boost.faces().len(), 6);
+        assert_eq!(Narrative::Ability.faces().len(), 8);
+        assert_eq!(Narrative::Challenge.faces().len(), 12);
+
+        let ability = Narrative::Ability.symbolic();
+
+        assert_eq!(Narrative::Proficiency.symbolic().face(12), &[Triumph]);
+        assert_eq!(ability.faces().len(), 8);
+        assert_eq!(ability.face(1), &[]);
+        assert_eq!(ability.face(9), &[]);
+        assert_eq!(ability.face(0), &[]);
+    }
+
+    #[test]
+    fn check_text() {
+        colored::control::set_override(false);
+
+        assert_eq!(Narrative::Boost.symbolic().text(1), "-");
+        assert_eq!(Narrative::Boost.symbolic().text(4), "sa");
+        assert_eq!(Narrative::Difficulty.symbolic().text(8), "ft");
+        assert_eq!(Narrative::Challenge.symbolic().text(12), "y");
+    }
+
+    #[test]
+    fn check_symbols() {
+        let triumph = Narrative::Proficiency.symbolic().symbols(12);
+
+        assert_eq!(triumph.success(), 1);
+        assert_eq!(triumph.triumph(), 1);
+        assert_eq!(Narrative::Challenge.symbolic().symbols(12).net(), -1);
+    }
+
+    #[test]
+    fn check_symbol_face() -> Result<()> {
+        assert_eq!(Symbol::face("sa")?, vec![Success, Advantage]);
+        assert_eq!(Symbol::face("y")?, vec![Despair]);
+        assert_eq!(Symbol::face("-")?, vec![]);
+        assert_eq!(Symbol::face("")?, vec![]);
+        assert!(Symbol::face("sq").is_err());
+
+        let force = Symbolic::new("force", vec![Symbol::face("-")?, Symbol::face("xx")?]);
+
+        assert_eq!(force.to_string(), "force");
+        assert_eq!(force.symbols(2).triumph(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_cancel() {
+        let symbols = Symbols::of(&[
+            Success, Success, Advantage, Failure, Threat, Threat, Triumph,
+        ]);
+        let cancelled = symbols.cancel();
+
+        assert_eq!(cancelled.success(), 2);
+        assert_eq!(cancelled.failure(), 0);
+        assert_eq!(cancelled.advantage(), 0);
+        assert_eq!(cancelled.threat(), 1);
+        assert_eq!(cancelled.triumph(), 1);
+        assert_eq!(cancelled.net(), 2);
+
+        let despair = Symbols::of(&[Success, Despair]).cancel();
+
+        assert_eq!(despair.net(), 0);
+        assert_eq!(despair.despair(), 1);
+    }
+
+    #[test]
+    fn check_sum() {
+        let total: Symbols = [
+            Symbols::of(&[Success]),
+            Symbols::of(&[Failure, Threat]),
+            Symbols::of(&[Advantage]),
+        ]
+        .into_iter()
+        .sum();
+
+        assert_eq!(total, Symbols::of(&[Success, Failure, Threat, Advantage]));
+    }
+
+    #[test]
+    fn check_display() -> Result<()> {
+        assert_eq!(
+            Symbols::of(&[Success, Success, Advantage, Triumph]).to_string(),
+            "3 successes, 1 advantage, 1 triumph"
+        );
+        assert_eq!(
+            Symbols::of(&[Failure, Threat, Threat]).to_string(),
+            "1 failure, 2 threat"
+        );
+        assert_eq!(Symbols::default().to_string(), "0 successes");
+        assert_eq!("ability".parse::<Narrative>()?, Narrative::Ability);
+        assert_eq!("Difficulty".parse::<Narrative>()?.to_string(), "Difficulty");
+        assert!("force".parse::<Narrative>().is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,238 @@
+use {
+    crate::dice::{narrative::Symbol, narrative::Symbolic, Dice},
+    anyhow::{anyhow, Context, Error, Result},
+    serde::Deserialize,
+    std::{
+        collections::BTreeMap,
+        fs,
+        path::{Path, PathBuf},
+        str::FromStr,
+    },
+};
+
+/// Names of the built-in dice, which a registered dice may not take.
+const BUILT_IN: [&str; 8] = [
+    "f",
+    "fate",
+    "boost",
+    "setback",
+    "ability",
+    "difficulty",
+    "proficiency",
+    "challenge",
+];
+
+/// Dice defined by name in a TOML file, such as `[dice.hit] faces = [0, 0, 0, 1, 1, 2]`, and
+/// rolled as `3dhit` or `3d[hit]`. Dice of symbols give the letters on each face instead,
+/// such as `[dice.force] symbols = ["y", "-", "sa"]`, reading `-` as a blank face.
+#[derive(Debug, PartialEq, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Registry {
+    #[serde(default)]
+    dice: BTreeMap<String, Definition>,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Definition {
+    faces: Option<Vec<i32>>,
+    symbols: Option<Vec<String>>,
+}
+
+impl Definition {
+    fn dice(&self, name: &str) -> Result<Dice> {
+        match (&self.faces, &self.symbols) {
+            (Some(faces), None) if !faces.is_empty() => {
+                Ok(Dice::Named(name.to_string(), faces.clone()))
+            }
+            (None, Some(symbols)) if !symbols.is_empty() => Ok(Dice::Symbolic(Symbolic::new(
+                name,
+                symbols
+                    .iter()
+                    .map(|face| Symbol::face(face))
+                    .collect::<Result<_>>()
+                    .with_context(|| format!("Unable to read the symbols of dice `{}`", name))?,
+            ))),
+            (Some(_), Some(_)) => Err(anyhow!(
+                "Dice `{}` needs either faces or symbols, not both",
+                name
+            )),
+            _ => Err(anyhow!(
+                "Dice `{}` needs at least one face or symbol face",
+                name
+            )),
+        }
+    }
+}
+
+impl Registry {
+    /// The file read when no other is given, `roll/dice.toml` in the user's config directory.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("roll").join("dice.toml"))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        fs::read_to_string(path)
+            .with_context(|| format!("Unable to read dice from {}", path.display()))?
+            .parse()
+            .with_context(|| format!("Unable to load dice from {}", path.display()))
+    }
+
+    pub fn with_dice(mut self, name: &str, faces: Vec<i32>) -> Self {
+        self.dice.insert(
+            name.to_string(),
+            Definition {
+                faces: Some(faces),
+                symbols: None,
+            },
+        );
+        self
+    }
+
+    /// The dice called `name`, if it is defined.
+    pub fn dice(&self, name: &str) -> Option<Dice> {
+        self.dice
+            .get(name)
+            .and_then(|definition| definition.dice(name).ok())
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.dice.keys()
+    }
+
+    fn check(&self) -> Result<()> {
+        for (name, definition) in &self.dice {
+            if !name.starts_with(|c: char| c.is_ascii_lowercase())
+                || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            {
+                return Err(anyhow!(
+                    "Dice name `{}` must start with a lowercase letter and hold only letters, digits and `_`",
+                    name
+                ));
+            }
+            if BUILT_IN.contains(&name.as_str()) {
+                return Err(anyhow!("Dice name `{}` is a built-in dice", name));
+            }
+            definition.dice(name)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Registry {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Registry> {
+        let registry: Registry = toml::from_str(s)?;
+        registry.check()?;
+        Ok(registry)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::{dice::narrative::Narrative, parse::RollParser},
+    };
+
+    #[test]
+    fn check_parse() -> Result<()> {
+        let registry: Registry = r#"
+            [dice.hit]
+            faces = [0, 0, 0, 1, 1, 2]
+
+            [dice.shield_2]
+            faces = [-1, 1]
+        "#
+        .parse()?;
+
+        assert_eq!(
+            registry.dice("hit"),
+            Some(Dice::Named("hit".to_string(), vec![0, 0, 0, 1, 1, 2]))
+        );
+        assert_eq!(
+            registry.dice("shield_2"),
+            Some(Dice::Named("shield_2".to_string(), vec![-1, 1]))
+        );
+        assert_eq!(registry.dice("miss"), None);
+        assert_eq!(registry.names().count(), 2);
+        assert_eq!("".parse::<Registry>()?, Registry::default());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_parse_errors() {
+        assert!("[dice.hit]\nfaces = []".parse::<Registry>().is_err());
+        assert!("[dice.Hit]\nfaces = [1]".parse::<Registry>().is_err());
+        assert!("[dice.2hit]\nfaces = [1]".parse::<Registry>().is_err());
+        assert!("[dice.fate]\nfaces = [1]".parse::<Registry>().is_err());
+        assert!("[dice.ability]\nsymbols = [\"s\"]"
+            .parse::<Registry>()
+            .is_err());
+        assert!("[dice.boosted]\nfaces = [1]".parse::<Registry>().is_ok());
+        assert!("[dice.hit]\nsides = [1]".parse::<Registry>().is_err());
+        assert!("[dice.hit]\nfaces = [\"a\"]".parse::<Registry>().is_err());
+        assert!("[dice.hit]\nsymbols = []".parse::<Registry>().is_err());
+        assert!("[dice.hit]\nsymbols = [\"q\"]".parse::<Registry>().is_err());
+        assert!("[dice.hit]\nfaces = [1]\nsymbols = [\"s\"]"
+            .parse::<Registry>()
+            .is_err());
+        assert!("[dice.hit]".parse::<Registry>().is_err());
+        assert!(Registry::load(Path::new("/no/such/dice.toml")).is_err());
+    }
+
+    #[test]
+    fn check_symbols() -> Result<()> {
+        let registry: Registry = r#"
+            [dice.force]
+            symbols = ["y", "-", "sa", "xx"]
+        "#
+        .parse()?;
+        let force = registry.dice("force").expect("force is defined");
+
+        assert_eq!(
+            force,
+            Dice::Symbolic(Symbolic::new(
+                "force",
+                vec![
+                    vec![Symbol::Despair],
+                    vec![],
+                    vec![Symbol::Success, Symbol::Advantage],
+                    vec![Symbol::Triumph, Symbol::Triumph],
+                ]
+            ))
+        );
+        assert_eq!(force.faces(), 1..=4);
+        assert_eq!(force.to_string(), "d[force]");
+        assert_eq!(
+            Dice::Symbolic(Symbolic::new("ability", vec![vec![Symbol::Success]])).to_string(),
+            "d[ability]"
+        );
+        assert_eq!(Dice::from(Narrative::Ability).to_string(), "dAbility");
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_expression_with() -> Result<()> {
+        let registry = Registry::default().with_dice("hit", vec![0, 0, 0, 1, 1, 2]);
+        let hit = Dice::Named("hit".to_string(), vec![0, 0, 0, 1, 1, 2]);
+
+        assert_eq!(Dice::parse_with(&registry, "[hit]")?, hit);
+        assert_eq!(
+            RollParser::expression_with(&registry, "3dhit + 1")?.to_string(),
+            "3d[hit] + 1"
+        );
+        assert!(RollParser::expression("3dhit").is_err());
+
+        let error = Dice::parse_with(&registry, "[unknown]")
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.contains("[dice.unknown]"), "{}", error);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+//! Dice notation parser and roller.
+//!
+//! An expression such as `4d6k3 + 2` is parsed into an [`Expression`], validated against the
+//! dice it rolls, then evaluated with any [`RngCore`] into a [`RollResult`].
+//!
+//! ```
+//! let mut rng = roll::seeded(42);
+//! let result = roll::roll("4d6k3", &mut rng)?;
+//!
+//! assert!((3..=18).contains(&result.total()));
+//! assert_eq!(result.rolls()[0].values().len(), 4);
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! Exact probabilities and simulations of an expression are available from [`stats`].
+
+pub mod dice;
+mod mock;
+pub mod parse;
+pub mod roll;
+pub mod stats;
+
+pub use crate::{
+    dice::{bound::Bounded, registry::Registry, Dice, Die},
+    parse::{error::ParseError, RollParser},
+    roll::{
+        behaviour::{Behaviour, DiscardDirection, RerollType, DEFAULT_LIMIT},
+        expression::{Evaluation, Expression, Operator},
+        outcome::{Outcome, Outcomes},
+        validate::ValidationError,
+        value::{Action, ExType, TensType, Value},
+        wild::Wild,
+        Roll,
+    },
+};
+
+use {
+    anyhow::Result,
+    rand::{RngCore, SeedableRng},
+    rand_chacha::ChaCha8Rng,
+};
+
+/// The dice thrown for an expression and its outcome, as returned by [`roll`](fn@roll).
+pub type RollResult = Evaluation;
+
+/// Generator for `seed`; rolling the same expression from the same seed repeats every die,
+/// reroll and explosion. ChaCha8 is a fixed algorithm, so a seed rolls the same on every
+/// platform and rand version.
+pub fn seeded(seed: u64) -> ChaCha8Rng {
+    ChaCha8Rng::seed_from_u64(seed)
+}
+
+/// Parse, validate and roll the dice `expression`.
+///
+/// Fails with a [`ParseError`] when the expression is not understood, a [`ValidationError`]
+/// when it can never be rolled, or an arithmetic error such as division by zero.
+pub fn roll(expression: &str, rng: &mut dyn RngCore) -> Result<RollResult> {
+    roll_with_limit(expression, DEFAULT_LIMIT, rng)
+}
+
+/// As [`roll`](fn@roll), stopping any reroll or explosion without a limit of its own after
+/// `limit` iterations of a die.
+pub fn roll_with_limit(
+    expression: &str,
+    limit: usize,
+    rng: &mut dyn RngCore,
+) -> Result<RollResult> {
+    let expression = RollParser::expression(expression)?;
+    expression.validate()?;
+    expression.evaluate_with_limit(limit, rng)
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, crate::mock::rng::*, anyhow::Result};
+
+    #[test]
+    fn check_roll() -> Result<()> {
+        let mut rng = rng(Dice::D6, 0);
+        let result = roll("4d6k3+1", &mut rng)?;
+
+        assert_eq!(result.total(), 2 + 3 + 4 + 1);
+        assert_eq!(result.rolls()[0].die(), &Die::new(Dice::D6, 4));
+        assert_eq!(result.to_string(), "[1 2 3 4] + 1 => 10");
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_roll_seeded() -> Result<()> {
+        let first = roll("10d6r1!k8 + 2d10", &mut seeded(1234))?;
+
+        assert_eq!(first, roll("10d6r1!k8 + 2d10", &mut seeded(1234))?);
+        assert_ne!(first, roll("10d6r1!k8 + 2d10", &mut seeded(4321))?);
+        // pinned, so a change of generator is caught before it changes published seeds
+        assert_eq!(
+            roll("6d6", &mut seeded(42))?.to_string(),
+            "[2 5 1 6 5 3] => 22"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_roll_with_limit() -> Result<()> {
+        let mut sixes = seq_rng(Dice::D6, [5].into_iter());
+        let result = roll_with_limit("1d6!", 2, &mut sixes)?;
+
+        assert_eq!(result.total(), 18);
+        assert_eq!(
+            result.rolls()[0].values()[0].actions().last(),
+            Some(&Action::Capped)
+        );
+        assert_eq!(roll("1d6!{1}", &mut sixes)?.total(), 12);
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_roll_errors() {
+        let mut rng = rng(Dice::D6, 0);
+
+        assert!(roll("2d6+", &mut rng)
+            .unwrap_err()
+            .downcast_ref::<ParseError>()
+            .is_some());
+        assert_eq!(
+            roll("2d6k5", &mut rng)
+                .unwrap_err()
+                .downcast_ref::<ValidationError>(),
+            Some(&ValidationError::KeepTooMany(Die::new(Dice::D6, 2), 5, 2))
+        );
+    }
+}
@@ -1,27 +1,157 @@
 mod cli;
-mod dice;
-mod mock;
-mod parse;
-mod roll;
 
 use {
-    crate::{cli::Args, parse::RollParser, roll::Roll},
+    crate::cli::{repl, Args, Format},
     anyhow::Result,
     clap::Parser,
-    rand::thread_rng,
+    colored::Colorize,
+    joinery::JoinableIterator,
+    rand::{thread_rng, Rng},
+    roll::{
+        seeded,
+        stats::{roll::criticals, simulate::simulate, Distribution},
+        Evaluation, Expression, Registry, RollParser,
+    },
 };
 
 fn main() -> Result<()> {
     let args = Args::parse();
-    let mut rng = thread_rng();
+    let seed = args.seed().unwrap_or_else(|| thread_rng().gen());
+    let registry = match args.dice() {
+        Some(path) => Registry::load(&path)?,
+        None => Registry::default(),
+    };
 
-    let (die, behaviour) = RollParser::roll(args.content().unwrap_or("20d10r1"))?;
+    let content = match args.content() {
+        Some(content) if !args.repl() => content,
+        content => {
+            return repl::run(
+                seed,
+                args.limit(),
+                registry,
+                args.history().as_deref(),
+                content,
+            );
+        }
+    };
+    let expression = match RollParser::expression_with(&registry, content) {
+        Ok(expression) => expression,
+        Err(error) => {
+            eprintln!("{}", error.render());
+            std::process::exit(2);
+        }
+    };
+    if let Err(error) = expression.validate() {
+        eprintln!("{} {}", "error:".red().bold(), error);
+        std::process::exit(2);
+    }
 
-    let mut roll = Roll::from_roll(&die, &mut rng);
+    if args.stats() {
+        return print_stats(&expression, args.limit());
+    }
+    if let Some(runs) = args.simulate() {
+        return print_simulation(&expression, runs, seed, args.limit());
+    }
 
-    roll.apply(behaviour, &mut rng);
+    let evaluation = expression.evaluate_with_limit(args.limit(), &mut seeded(seed))?;
 
-    println!("{}", roll);
+    match args.format() {
+        Format::Text => {
+            if args.replay() {
+                print_replay(&evaluation);
+            }
+            println!("{} (seed {})", evaluation, seed);
+        }
+        Format::Json => {
+            let mut json = serde_json::to_value(&evaluation)?;
+            json["seed"] = seed.into();
+            println!("{}", json);
+        }
+    }
 
     Ok(())
 }
+
+fn print_stats(expression: &Expression, limit: usize) -> Result<()> {
+    let distribution = Distribution::of_with_limit(expression, limit)?;
+
+    println!("{}", expression);
+    print_summary(&distribution);
+    for group in expression.groups() {
+        if let Expression::Roll(die, behaviours, _) = group {
+            if let Some(criticals) = criticals(die, behaviours, limit)? {
+                println!(
+                    "{} criticals: failures mean {:.4} (at least one {:.4}%), successes mean {:.4} (at least one {:.4}%)",
+                    group,
+                    criticals.failures().mean(),
+                    criticals.failures().at_least(1) * 100.0,
+                    criticals.successes().mean(),
+                    criticals.successes().at_least(1) * 100.0,
+                );
+            }
+        }
+    }
+    println!("{:>6} {:>9} {:>9} {:>9}", "value", "=", ">=", "<=");
+    print!("{}", distribution);
+
+    Ok(())
+}
+
+fn print_simulation(expression: &Expression, runs: u64, seed: u64, limit: usize) -> Result<()> {
+    let histogram = simulate(expression, runs, seed, limit)?;
+
+    println!(
+        "{} over {} runs (seed {})",
+        expression,
+        histogram.runs(),
+        seed
+    );
+    print_summary(&histogram.distribution());
+    print!("{}", histogram);
+
+    Ok(())
+}
+
+fn print_replay(evaluation: &Evaluation) {
+    let mut rolls = evaluation.rolls().iter();
+    for group in evaluation.expression().groups() {
+        println!("{}", group);
+        // a trait roll throws its trait and wild dice as separate groups
+        let count = match group {
+            Expression::Wild(wild) => wild.dies().len(),
+            _ => 1,
+        };
+        let values = rolls.by_ref().take(count).flat_map(|r| r.values());
+        for (index, value) in values.enumerate() {
+            if value.actions().is_empty() {
+                println!("  die {}: {}", index + 1, value.value());
+            } else {
+                println!(
+                    "  die {}: {} ({})",
+                    index + 1,
+                    value.value(),
+                    value.actions().iter().join_with(", ")
+                );
+            }
+        }
+    }
+}
+
+fn print_summary(distribution: &Distribution) {
+    println!(
+        "mean {:.4}, variance {:.4}, std dev {:.4}, range {}..={}",
+        distribution.mean(),
+        distribution.variance(),
+        distribution.std_dev(),
+        distribution.min().unwrap_or_default(),
+        distribution.max().unwrap_or_default(),
+    );
+    println!(
+        "percentiles 5%: {}, 25%: {}, 50%: {}, 75%: {}, 95%: {}",
+        distribution.percentile(5.0).unwrap_or_default(),
+        distribution.percentile(25.0).unwrap_or_default(),
+        distribution.percentile(50.0).unwrap_or_default(),
+        distribution.percentile(75.0).unwrap_or_default(),
+        distribution.percentile(95.0).unwrap_or_default(),
+    );
+}
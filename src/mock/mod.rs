@@ -7,7 +7,7 @@ pub(crate) mod rng {
     };
 
     fn increment(dice: Dice) -> u64 {
-        let length = 1 + (*dice.faces().end() - *dice.faces().start()) as u32;
+        let length = dice.sides().len() as u32;
         1 + (u32::MAX / length) as u64
     }
 
@@ -83,9 +83,9 @@ pub(crate) mod rng {
             for dice in [D2, D3, D4, D6, D8, D10, D12, D20] {
                 println!(">>>>> {} ", dice);
                 for i in dice.faces() {
-                    let mut rng = rng(dice, i as u64);
+                    let mut rng = rng(dice.clone(), i as u64);
                     for _ in 0..30 {
-                        print!("{:02} ", dice.roll(&mut rng));
+                        print!("{:02} ", dice.roll(&mut rng).unwrap());
                     }
                     println!();
                 }
@@ -97,9 +97,9 @@ pub(crate) mod rng {
             for dice in [D6] {
                 println!(">>>>> Step {} ", dice);
                 for i in dice.faces() {
-                    let mut rng = step_rng(dice, i as u64, 2);
+                    let mut rng = step_rng(dice.clone(), i as u64, 2);
                     for _ in 0..30 {
-                        print!("{:02} ", dice.roll(&mut rng));
+                        print!("{:02} ", dice.roll(&mut rng).unwrap());
                     }
                     println!();
                 }
@@ -112,9 +112,9 @@ pub(crate) mod rng {
                 println!(">>>>> Seq {} ", dice);
                 let v: Vec<u64> = vec![1, 2, 3, 4, 5, 6, 7];
                 for _ in dice.faces() {
-                    let mut rng = seq_rng(dice, v.iter().cloned());
+                    let mut rng = seq_rng(dice.clone(), v.iter().cloned());
                     for _ in 0..30 {
-                        print!("{:02} ", dice.roll(&mut rng));
+                        print!("{:02} ", dice.roll(&mut rng).unwrap());
                     }
                     println!();
                 }
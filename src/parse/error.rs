@@ -0,0 +1,308 @@
+use {
+    crate::parse::Rule,
+    colored::Colorize,
+    pest::error::{Error, ErrorVariant, InputLocation},
+    std::{
+        error,
+        fmt::{self, Display},
+        ops::Range,
+    },
+};
+
+/// Failure to parse a roll expression, locating the offending part of the input.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParseError {
+    input: String,
+    span: Range<usize>,
+    message: String,
+    expected: Vec<String>,
+    hint: Option<String>,
+}
+
+impl ParseError {
+    pub fn new(input: &str, span: Range<usize>, message: String) -> Self {
+        Self {
+            input: input.to_string(),
+            span,
+            message,
+            expected: Vec::new(),
+            hint: None,
+        }
+    }
+
+    pub fn with_expected(mut self, expected: Vec<String>) -> Self {
+        self.expected = expected;
+        self
+    }
+
+    pub fn with_hint(mut self, hint: Option<String>) -> Self {
+        self.hint = hint;
+        self
+    }
+
+    pub(crate) fn from_pest(input: &str, error: Error<Rule>) -> Self {
+        let span = match error.location {
+            InputLocation::Pos(pos) => pos..pos,
+            InputLocation::Span((start, end)) => start..end,
+        };
+        let expected = match &error.variant {
+            ErrorVariant::ParsingError { positives, .. } => {
+                let mut expected: Vec<String> = Vec::new();
+                for rule in positives {
+                    if let Some(text) = Self::describe(rule) {
+                        if !expected.iter().any(|e| e == text) {
+                            expected.push(text.to_string());
+                        }
+                    }
+                }
+                expected
+            }
+            ErrorVariant::CustomError { .. } => Vec::new(),
+        };
+        let message = match (expected.split_last(), input[span.start..].chars().next()) {
+            (Some((last, [])), Some(c)) => format!("found `{}`, expected {}", c, last),
+            (Some((last, [])), None) => format!("input ended, expected {}", last),
+            (Some((last, rest)), Some(c)) => {
+                format!("found `{}`, expected {} or {}", c, rest.join(", "), last)
+            }
+            (Some((last, rest)), None) => {
+                format!("input ended, expected {} or {}", rest.join(", "), last)
+            }
+            (None, _) => error.variant.message().to_string(),
+        };
+        let hint = Self::suggest(input, span.start, &expected);
+
+        Self::new(input, span, message)
+            .with_expected(expected)
+            .with_hint(hint)
+    }
+
+    /// The same error against `input`, where `position` maps each position of the text that
+    /// was parsed into `input`.
+    pub(crate) fn relocate(mut self, input: &str, position: impl Fn(usize) -> usize) -> Self {
+        self.input = input.to_string();
+        self.span = position(self.span.start)..position(self.span.end);
+        self
+    }
+
+    pub fn span(&self) -> &Range<usize> {
+        &self.span
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn expected(&self) -> &Vec<String> {
+        &self.expected
+    }
+
+    pub fn hint(&self) -> Option<&str> {
+        self.hint.as_deref()
+    }
+
+    /// Render the input with a caret under the offending characters, followed by any hint.
+    pub fn render(&self) -> String {
+        let offset = self.input[..self.span.start].chars().count();
+        let width = self.input[self.span.clone()].chars().count().max(1);
+        let mut text = format!(
+            "{} {}\n  {}\n  {}{}",
+            "error:".red().bold(),
+            self.message,
+            self.input,
+            " ".repeat(offset),
+            "^".repeat(width).red().bold()
+        );
+        if let Some(hint) = &self.hint {
+            text.push_str(&format!("\n{} {}", "hint:".cyan().bold(), hint));
+        }
+        text
+    }
+
+    /// Hint for an expression element that matched the grammar but could not be understood.
+    pub(crate) fn hint_for(rule: Rule) -> Option<String> {
+        match rule {
+            Rule::die | Rule::dice => {
+                Some("dice need sides: try `d6`, `d%`, `dF` or a name from the dice config")
+            }
+            Rule::reroll => {
+                Some("reroll takes an optional point: try `r`, `ro1`, `rh<3` or `r%>50`")
+            }
+            Rule::explode => {
+                Some("explode takes an optional type and point: try `!`, `!!p>=5` or `!{3}`")
+            }
+            Rule::tens => Some("bonus and penalty dice take a count: try `+b`, `+2b` or `+p`"),
+            Rule::discard => Some("keep and drop need a count: try `k3` or `dl1`"),
+            Rule::critical => Some("critical needs a type: try `cs`, `cs19` or `cf2`"),
+            Rule::target => Some("target needs a point: try `t8`, `t>7` or `t8:9`"),
+            Rule::failure => Some("failures need a point: try `t8f1`"),
+            Rule::exceptional => Some("exceptional successes need a count: try `t8e5`"),
+            Rule::skill => Some("a skill roll needs its value: try `<=65`"),
+            Rule::number => Some("numbers must fit in 32 bits"),
+            Rule::modifier => Some("a trait roll modifier needs a number: try `sw(d8+2)`"),
+            _ => None,
+        }
+        .map(String::from)
+    }
+
+    fn describe(rule: &Rule) -> Option<&'static str> {
+        match rule {
+            Rule::expression
+            | Rule::term
+            | Rule::roll
+            | Rule::wild
+            | Rule::number
+            | Rule::negate => Some("a number or roll such as `2d6`"),
+            Rule::die | Rule::dice => Some("dice such as `d6`"),
+            Rule::additive | Rule::multiplicative => Some("an operator"),
+            Rule::reroll | Rule::tens | Rule::explode | Rule::discard | Rule::critical => {
+                Some("a modifier such as `k3` or `!`")
+            }
+            Rule::target | Rule::matching | Rule::skill | Rule::hits => {
+                Some("an outcome such as `t8`")
+            }
+            Rule::failure | Rule::exceptional => Some("pool failures such as `f1`"),
+            Rule::EOI => Some("the end of the input"),
+            _ => None,
+        }
+    }
+
+    fn suggest(input: &str, position: usize, expected: &[String]) -> Option<String> {
+        let (before, after) = input.split_at(position);
+        let opened = input.matches('(').count();
+        let closed = input.matches(')').count();
+
+        if after.starts_with(')') && closed > opened {
+            Some("this bracket has no matching `(`".to_string())
+        } else if after.is_empty() && opened > closed {
+            Some("a bracket is never closed: add `)`".to_string())
+        } else if before.ends_with(char::is_whitespace)
+            && before.trim_end().ends_with(|c: char| c.is_ascii_digit())
+            && after.starts_with(['d', 'D'])
+        {
+            Some("the count and dice go together: try `2d6` rather than `2 d6`".to_string())
+        } else if before.trim_end().ends_with(['+', '-', '*', '/']) {
+            Some("an operator must be followed by a number or a roll: try `2d6+1`".to_string())
+        } else if expected.is_empty() || after.is_empty() {
+            None
+        } else {
+            Some("a roll looks like `4d6k3`, and can be combined with `+ - * /`".to_string())
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.span.start + 1)?;
+        if let Some(hint) = &self.hint {
+            write!(f, " ({})", hint)?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for ParseError {}
+
+#[cfg(test)]
+mod test {
+    use {super::*, crate::parse::RollParser};
+
+    fn parse_error(input: &str) -> ParseError {
+        RollParser::expression(input).unwrap_err()
+    }
+
+    #[test]
+    fn check_incomplete_operator() {
+        let error = parse_error("2d6+");
+
+        assert_eq!(error.span(), &(4..4));
+        assert!(error
+            .expected()
+            .contains(&"a number or roll such as `2d6`".to_string()));
+        assert_eq!(
+            error.message(),
+            "input ended, expected a number or roll such as `2d6`"
+        );
+        assert_eq!(
+            error.hint(),
+            Some("an operator must be followed by a number or a roll: try `2d6+1`")
+        );
+    }
+
+    #[test]
+    fn check_unclosed_bracket() {
+        let error = parse_error("(2d6+1");
+
+        assert_eq!(error.span(), &(6..6));
+        assert_eq!(error.hint(), Some("a bracket is never closed: add `)`"));
+    }
+
+    #[test]
+    fn check_unmatched_bracket() {
+        let error = parse_error("2d6+1)");
+
+        assert_eq!(error.span(), &(5..5));
+        assert!(error.message().starts_with("found `)`"));
+        assert_eq!(error.hint(), Some("this bracket has no matching `(`"));
+    }
+
+    #[test]
+    fn check_spaced_roll() {
+        let error = parse_error("2 d6");
+
+        assert_eq!(error.span(), &(2..2));
+        assert_eq!(
+            error.message(),
+            "found `d`, expected the end of the input or an operator"
+        );
+        assert_eq!(
+            error.hint(),
+            Some("the count and dice go together: try `2d6` rather than `2 d6`")
+        );
+    }
+
+    #[test]
+    fn check_invalid_behaviour() {
+        let error = parse_error("4d6k+1");
+
+        assert_eq!(error.span(), &(3..4));
+        assert_eq!(
+            error.hint(),
+            Some("keep and drop need a count: try `k3` or `dl1`")
+        );
+
+        let error = parse_error("1d20c");
+
+        assert_eq!(error.span(), &(4..5));
+        assert_eq!(
+            error.hint(),
+            Some("critical needs a type: try `cs`, `cs19` or `cf2`")
+        );
+    }
+
+    #[test]
+    fn check_number_too_large() {
+        let error = parse_error("1d6+99999999999");
+
+        assert_eq!(error.span(), &(4..15));
+        assert_eq!(error.hint(), Some("numbers must fit in 32 bits"));
+    }
+
+    #[test]
+    fn check_render() {
+        colored::control::set_override(false);
+
+        assert_eq!(
+            parse_error("4d6k").render(),
+            "error: unable to understand `k`: cannot parse integer from empty string\n  4d6k\n     ^\nhint: keep and drop need a count: try `k3` or `dl1`"
+        );
+    }
+
+    #[test]
+    fn check_display() {
+        assert_eq!(
+            parse_error("1d20c").to_string(),
+            "unable to understand `c`: Unable to parse c as Behaviour at position 5 (critical needs a type: try `cs`, `cs19` or `cf2`)"
+        );
+    }
+}
@@ -1,42 +1,529 @@
+pub mod error;
+
 use {
-    crate::{dice::Die, roll::behaviour::Behaviour},
-    anyhow::Result,
-    pest::Parser,
+    crate::{
+        dice::{registry::Registry, Dice, Die},
+        parse::error::ParseError,
+        roll::{
+            behaviour::Behaviour,
+            expression::{Expression, Operator},
+            outcome::Outcomes,
+            wild::Wild,
+        },
+    },
+    pest::{
+        iterators::{Pair, Pairs},
+        Parser,
+    },
     pest_derive::Parser,
+    std::{fmt::Display, ops::Range, str::FromStr},
 };
 
 #[derive(Parser)]
 #[grammar = "parse/roll.pest"]
 pub struct RollParser {}
 
+type Result<T> = std::result::Result<T, ParseError>;
+
 impl RollParser {
-    pub fn roll(s: &str) -> Result<(Die, Vec<Behaviour>)> {
-        let mut roll = RollParser::parse(Rule::roll, s)?;
+    /// Parse `s` knowing only the built-in dice.
+    pub fn expression(s: &str) -> Result<Expression> {
+        Self::expression_with(&Registry::default(), s)
+    }
+
+    /// Parse `s`, reading any named dice such as `3dhit` or `3d[hit]` from `registry`.
+    pub fn expression_with(registry: &Registry, s: &str) -> Result<Expression> {
+        let (text, inserted) = Self::bracket_names(registry, s);
+        Self::calculation(registry, &text).map_err(|error| {
+            error.relocate(s, |position| {
+                position - inserted.iter().filter(|i| **i < position).count()
+            })
+        })
+    }
+
+    /// Bracket each registered name that follows the `d` of a die, as `3dhitk2` to `3d[hit]k2`,
+    /// so that the grammar can tell where the name ends, along with the positions of the
+    /// brackets added. The longest registered name wins.
+    fn bracket_names(registry: &Registry, s: &str) -> (String, Vec<usize>) {
+        let mut text = String::new();
+        let mut inserted = Vec::new();
+        let mut rest = s;
+        while let Some(d) = rest.find(['d', 'D']) {
+            let (before, after) = rest.split_at(d + 1);
+            text.push_str(before);
+            rest = after;
+            // a die starts an operand, with at most a count before its `d`
+            let count = text[..text.len() - 1].trim_end_matches(|c: char| c.is_ascii_digit());
+            if !count.is_empty() && !count.ends_with(['(', '+', '-', '*', '/', ' ', '\t']) {
+                continue;
+            }
+            if let Some(name) = registry
+                .names()
+                .filter(|name| rest.starts_with(name.as_str()))
+                .max_by_key(|name| name.len())
+            {
+                inserted.push(text.len());
+                text.push('[');
+                text.push_str(name);
+                inserted.push(text.len());
+                text.push(']');
+                rest = &rest[name.len()..];
+            }
+        }
+        text.push_str(rest);
+        (text, inserted)
+    }
+
+    fn calculation(registry: &Registry, s: &str) -> Result<Expression> {
+        let mut calculation =
+            RollParser::parse(Rule::calculation, s).map_err(|e| ParseError::from_pest(s, e))?;
+
+        match calculation.next() {
+            Some(pair) => Self::parse_expression(registry, s, pair),
+            None => Err(ParseError::new(
+                s,
+                0..s.len(),
+                "empty expression".to_string(),
+            )),
+        }
+    }
+
+    fn parse_roll(registry: &Registry, s: &str, pair: Pair<Rule>) -> Result<Expression> {
+        let span = pair.as_span();
         let mut die: Option<Die> = None;
         let mut behaviours: Vec<Behaviour> = Vec::new();
+        let mut outcomes = Outcomes::default();
 
-        let r = roll.next().unwrap();
+        for record in pair.into_inner() {
+            match record.as_rule() {
+                Rule::die => {
+                    die = Some(Self::locate(
+                        s,
+                        &record,
+                        Die::parse_with(registry, record.as_str()),
+                    )?)
+                }
+                Rule::target | Rule::matching | Rule::skill | Rule::hits => {
+                    outcomes = Self::parse_pair(s, &record, &record.as_str().to_lowercase())?
+                }
+                // extend the target into a success pool, past the leading `f` or `e`
+                Rule::failure => {
+                    let failure =
+                        Self::locate(s, &record, Outcomes::failure(&record.as_str()[1..]))?;
+                    outcomes = Self::locate(s, &record, outcomes.with_failure(failure))?
+                }
+                Rule::exceptional => {
+                    let exceptional = Self::parse_pair(s, &record, &record.as_str()[1..])?;
+                    outcomes = Self::locate(s, &record, outcomes.with_exceptional(exceptional))?
+                }
+                _ => behaviours.push(Self::parse_pair(s, &record, record.as_str())?),
+            }
+        }
 
-        for record in r.into_inner() {
+        match die {
+            Some(die) => Ok(Expression::Roll(die, behaviours, outcomes)),
+            None => Err(Self::error(s, &pair_span(span), "roll is missing its dice")),
+        }
+    }
+
+    fn parse_wild(registry: &Registry, s: &str, pair: Pair<Rule>) -> Result<Expression> {
+        let span = pair.as_span();
+        let mut wild: Option<Wild> = None;
+
+        for record in pair.into_inner() {
             match record.as_rule() {
-                Rule::die => die = Some(record.as_str().parse()?),
-                _ => behaviours.push(record.as_str().parse()?),
+                // the dice text keeps its leading `d`
+                Rule::dice => {
+                    wild = Some(Wild::new(Self::locate(
+                        s,
+                        &record,
+                        Dice::parse_with(registry, &record.as_str()[1..]),
+                    )?))
+                }
+                Rule::modifier => {
+                    let modifier = Self::parse_pair(s, &record, record.as_str())?;
+                    wild = wild.map(|w| w.with_modifier(modifier));
+                }
+                Rule::number => {
+                    let target = Self::parse_pair(s, &record, record.as_str())?;
+                    wild = wild.map(|w| w.with_target(target));
+                }
+                _ => (),
             }
         }
 
-        Ok((die.unwrap(), behaviours))
+        match wild {
+            Some(wild) => Ok(Expression::Wild(wild)),
+            None => Err(Self::error(
+                s,
+                &pair_span(span),
+                "trait roll is missing its dice",
+            )),
+        }
+    }
+
+    fn parse_expression(registry: &Registry, s: &str, pair: Pair<Rule>) -> Result<Expression> {
+        match pair.as_rule() {
+            Rule::expression | Rule::term => {
+                let span = pair.as_span();
+                Self::parse_binary(registry, s, &pair_span(span), pair.into_inner())
+            }
+            Rule::negate => {
+                let span = pair_span(pair.as_span());
+                match pair.into_inner().next() {
+                    Some(inner) => Ok(Expression::Negate(Box::new(Self::parse_expression(
+                        registry, s, inner,
+                    )?))),
+                    None => Err(Self::error(s, &span, "`-` needs something to negate")),
+                }
+            }
+            Rule::number => Ok(Expression::Constant(Self::parse_pair(
+                s,
+                &pair,
+                pair.as_str(),
+            )?)),
+            Rule::roll => Self::parse_roll(registry, s, pair),
+            Rule::wild => Self::parse_wild(registry, s, pair),
+            _ => Err(Self::error(
+                s,
+                &pair_span(pair.as_span()),
+                &format!("unable to parse `{}` as an expression", pair.as_str()),
+            )),
+        }
+    }
+
+    fn parse_binary(
+        registry: &Registry,
+        s: &str,
+        span: &Range<usize>,
+        mut pairs: Pairs<Rule>,
+    ) -> Result<Expression> {
+        let mut lhs = match pairs.next() {
+            Some(pair) => Self::parse_expression(registry, s, pair)?,
+            None => return Err(Self::error(s, span, "expected an expression")),
+        };
+        while let (Some(operator), Some(rhs)) = (pairs.next(), pairs.next()) {
+            let operator: Operator = Self::parse_pair(s, &operator, operator.as_str())?;
+            lhs = Expression::binary(operator, lhs, Self::parse_expression(registry, s, rhs)?);
+        }
+        Ok(lhs)
+    }
+
+    /// Parse the text of a matched pair, locating any failure at the pair's span.
+    fn parse_pair<T>(s: &str, pair: &Pair<Rule>, text: &str) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        Self::locate(s, pair, text.parse())
+    }
+
+    /// Locate any failure to read a matched pair at the pair's span.
+    fn locate<T, E: Display>(
+        s: &str,
+        pair: &Pair<Rule>,
+        result: std::result::Result<T, E>,
+    ) -> Result<T> {
+        result.map_err(|e| {
+            ParseError::new(
+                s,
+                pair_span(pair.as_span()),
+                format!("unable to understand `{}`: {}", pair.as_str(), e),
+            )
+            .with_hint(ParseError::hint_for(pair.as_rule()))
+        })
+    }
+
+    fn error(s: &str, span: &Range<usize>, message: &str) -> ParseError {
+        ParseError::new(s, span.clone(), message.to_string())
     }
 }
 
+fn pair_span(span: pest::Span) -> Range<usize> {
+    span.start()..span.end()
+}
+
 #[cfg(test)]
 mod test {
-    use super::*;
+    use {
+        super::*,
+        crate::{
+            dice::bound::Bounded,
+            roll::behaviour::{DiscardDirection, RerollType},
+        },
+        anyhow::Result,
+    };
 
     #[test]
     fn check_parse_dice() {
         let result = RollParser::parse(Rule::roll, "d1:8");
 
         println!("{:?}", result);
-        assert!(matches!(result, Ok(_)));
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn check_parse_expression() -> Result<()> {
+        assert_eq!(
+            RollParser::expression("2d6+1d4+3")?,
+            Expression::binary(
+                Operator::Add,
+                Expression::binary(
+                    Operator::Add,
+                    Expression::Roll(Die::new(Dice::D6, 2), Vec::new(), Outcomes::Total),
+                    Expression::Roll(Die::new(Dice::D4, 1), Vec::new(), Outcomes::Total),
+                ),
+                Expression::Constant(3),
+            )
+        );
+        assert_eq!(
+            RollParser::expression("1d20 + 5 - 1d4")?,
+            Expression::binary(
+                Operator::Subtract,
+                Expression::binary(
+                    Operator::Add,
+                    Expression::Roll(Die::new(Dice::D20, 1), Vec::new(), Outcomes::Total),
+                    Expression::Constant(5),
+                ),
+                Expression::Roll(Die::new(Dice::D4, 1), Vec::new(), Outcomes::Total),
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_parse_expression_precedence() -> Result<()> {
+        assert_eq!(
+            RollParser::expression("1+2*3")?,
+            Expression::binary(
+                Operator::Add,
+                Expression::Constant(1),
+                Expression::binary(
+                    Operator::Multiply,
+                    Expression::Constant(2),
+                    Expression::Constant(3)
+                ),
+            )
+        );
+        assert_eq!(
+            RollParser::expression("(1+2)*3")?,
+            Expression::binary(
+                Operator::Multiply,
+                Expression::binary(
+                    Operator::Add,
+                    Expression::Constant(1),
+                    Expression::Constant(2)
+                ),
+                Expression::Constant(3),
+            )
+        );
+        assert_eq!(
+            RollParser::expression("-d4/2")?,
+            Expression::binary(
+                Operator::Divide,
+                Expression::Negate(Box::new(Expression::Roll(
+                    Die::new(Dice::D4, 1),
+                    Vec::new(),
+                    Outcomes::Total
+                ))),
+                Expression::Constant(2),
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_parse_expression_behaviours() -> Result<()> {
+        assert_eq!(
+            RollParser::expression("4d6k3+1d8!")?,
+            Expression::binary(
+                Operator::Add,
+                Expression::Roll(
+                    Die::new(Dice::D6, 4),
+                    vec![Behaviour::Keep(3, DiscardDirection::High)],
+                    Outcomes::Total
+                ),
+                Expression::Roll(Die::new(Dice::D8, 1), vec!["!".parse()?], Outcomes::Total),
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_parse_expression_outcomes() -> Result<()> {
+        assert_eq!(
+            RollParser::expression("6d10!t8")?,
+            Expression::Roll(
+                Die::new(Dice::D10, 6),
+                vec!["!".parse()?],
+                Outcomes::Target(Bounded::range_from(8))
+            )
+        );
+        assert_eq!(
+            RollParser::expression("5d10m")?,
+            Expression::Roll(Die::new(Dice::D10, 5), Vec::new(), Outcomes::Match)
+        );
+        assert!(RollParser::expression("5d10mt8").is_err());
+        assert_eq!(
+            RollParser::expression("6d10r<2t7:9")?,
+            Expression::Roll(
+                Die::new(Dice::D10, 6),
+                vec![Behaviour::Reroll(
+                    Some(Bounded::range_below(2)),
+                    RerollType::Repeat,
+                    None,
+                )],
+                Outcomes::Target(Bounded::range_between(7, 9))
+            )
+        );
+        assert_eq!(
+            RollParser::expression("8d10t8x10f1")?,
+            Expression::Roll(
+                Die::new(Dice::D10, 8),
+                vec!["!10".parse()?],
+                Outcomes::Pool(Bounded::range_from(8), Some(Bounded::range_to(1)), None)
+            )
+        );
+        assert_eq!(
+            RollParser::expression("5d10t8e3")?,
+            Expression::Roll(
+                Die::new(Dice::D10, 5),
+                Vec::new(),
+                Outcomes::Pool(Bounded::range_from(8), None, Some(3))
+            )
+        );
+        assert_eq!(
+            RollParser::expression("sw(d10)t6")?,
+            Expression::Wild(Wild::new(Dice::D10).with_target(6))
+        );
+        assert_eq!(
+            RollParser::expression("sw(d8+2)t6")?,
+            Expression::Wild(Wild::new(Dice::D8).with_modifier(2).with_target(6))
+        );
+        assert_eq!(
+            RollParser::expression("sw(d8-1)")?,
+            Expression::Wild(Wild::new(Dice::D8).with_modifier(-1))
+        );
+        assert!(RollParser::expression("sw(2d10)").is_err());
+        assert!(RollParser::expression("sw(d8+)").is_err());
+        assert!(RollParser::expression("sw(d8+1d4)").is_err());
+        assert!(RollParser::expression("5d10t8f").is_err());
+        assert!(RollParser::expression("5d10mf1").is_err());
+        assert!(RollParser::expression("6d10t").is_err());
+        assert!(RollParser::expression("6d10t<").is_err());
+        assert!(RollParser::expression("4d6k>3").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_parse_expression_named() -> Result<()> {
+        let registry = Registry::default()
+            .with_dice("hit", vec![0, 0, 0, 1, 1, 2])
+            .with_dice("h1", vec![1]);
+        let hit = Die::new(Dice::Named("hit".to_string(), vec![0, 0, 0, 1, 1, 2]), 3);
+
+        assert_eq!(
+            RollParser::expression_with(&registry, "3dhit")?,
+            Expression::Roll(hit.clone(), Vec::new(), Outcomes::Total)
+        );
+        assert_eq!(
+            RollParser::expression_with(&registry, "3dhitk2")?,
+            Expression::Roll(
+                hit.clone(),
+                vec![Behaviour::Keep(2, DiscardDirection::High)],
+                Outcomes::Total
+            )
+        );
+        assert_eq!(
+            RollParser::expression_with(&registry, "3dhitr0")?,
+            Expression::Roll(hit.clone(), vec!["r0".parse()?], Outcomes::Total)
+        );
+        assert_eq!(
+            RollParser::expression_with(&registry, "3dhitt2")?,
+            Expression::Roll(
+                hit.clone(),
+                Vec::new(),
+                Outcomes::Target(Bounded::range_from(2))
+            )
+        );
+        assert_eq!(
+            RollParser::expression_with(&registry, "3dhitm")?,
+            Expression::Roll(hit.clone(), Vec::new(), Outcomes::Match)
+        );
+        assert_eq!(
+            RollParser::expression_with(&registry, "3d[hit]k2")?,
+            RollParser::expression_with(&registry, "3dhitk2")?
+        );
+        // only the `d` of a die starts a name, not a drop
+        assert_eq!(
+            RollParser::expression_with(&registry, "4d6dh1")?,
+            Expression::Roll(
+                Die::new(Dice::D6, 4),
+                vec![Behaviour::Drop(1, DiscardDirection::High)],
+                Outcomes::Total
+            )
+        );
+        assert!(RollParser::expression_with(&registry, "3d[Hit]").is_err());
+
+        let error = RollParser::expression_with(&registry, "1dhit + 3dmiss").unwrap_err();
+
+        assert!(
+            error.message().contains("Unknown dice `miss`"),
+            "{}",
+            error.message()
+        );
+        assert_eq!(error.span(), &(8..14));
+        assert_eq!(
+            RollParser::expression_with(&registry, "2dhit + 1d6+")
+                .unwrap_err()
+                .span(),
+            &(12..12)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_parse_expression_round_trip() -> Result<()> {
+        for text in [
+            "2d6 + 1d4 + 3",
+            "(1d20 + 5) * 2",
+            "1 - (2 - 3)",
+            "4d6k3 - -2",
+            "6d10t8 + 1",
+            "4d6r<3 + 1d20cs>18",
+            "8d10!>9t>7 - 2",
+            "3d6!2:3t=6",
+            "4d6!=6r=1",
+            "2d6ro2 + 1d20rh<10",
+            "1d%r%l>=50 + 2d%",
+            "1d100+b<=65 + 1d%+2p<=40",
+            "2d66k1 + 1d666 + 1d88m",
+            "8d10!10t8f1 + 5d10t>7f1e3",
+            "12d6!h + 4d6h",
+            "sw(d8) + sw(d12)t6",
+            "sw(d8+2) + sw(d6-1)t6",
+            "1d6!{3} + 3d6r<3{2}",
+            "3d{1,1,2,3,5,8}k2 + 1d{-2,0,0,2}",
+            "2dAbility + 1dProficiency + 2dDifficulty",
+        ] {
+            assert_eq!(RollParser::expression(text)?.to_string(), text);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_parse_expression_invalid() {
+        assert!(RollParser::expression("4d6k").is_err());
+        assert!(RollParser::expression("1d20c").is_err());
+        assert!(RollParser::expression("2d6+").is_err());
+        assert!(RollParser::expression("(2d6").is_err());
+        assert!(RollParser::expression("2 d6").is_err());
+        assert!(RollParser::expression("").is_err());
     }
 }
@@ -1,102 +1,198 @@
 use {
     crate::{
         dice::{bound::Bounded, Dice},
-        roll::value::{Action, ExType, Value},
+        roll::value::{Action, ExType, TensType, Value},
     },
     anyhow::{anyhow, Error, Result},
     rand::RngCore,
-    std::{iter::Iterator, ops::RangeBounds, str::FromStr},
+    serde::Serialize,
+    std::{
+        fmt::{self, Display},
+        iter::Iterator,
+        ops::RangeBounds,
+        str::FromStr,
+    },
 };
 
-#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone, Copy)]
+#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone, Copy, Serialize)]
 pub enum DiscardDirection {
     High,
     Low,
 }
 
+#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone, Copy, Serialize)]
+pub enum RerollType {
+    Repeat,
+    Once,
+    High,
+    Low,
+}
+
 #[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone, Copy)]
 pub enum DiscardType {
     Keep(DiscardDirection),
     Drop(DiscardDirection),
 }
 
-#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone, Copy)]
+#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone, Copy, Serialize)]
 pub enum Behaviour {
-    Reroll(Option<Bounded>, bool),
-    Explode(Option<Bounded>, ExType),
+    Reroll(Option<Bounded>, RerollType, Option<usize>),
+    /// Reroll only the tens die of a percentile dice.
+    RerollTens(Option<Bounded>, RerollType),
+    /// Roll extra tens dice for a percentile dice and keep the lowest result.
+    Bonus(usize),
+    /// Roll extra tens dice for a percentile dice and keep the highest result.
+    Penalty(usize),
+    Explode(Option<Bounded>, ExType, Option<usize>),
     Critical(Option<Bounded>, Option<Bounded>),
     Keep(usize, DiscardDirection),
     Drop(usize, DiscardDirection),
 }
 
+/// Most rerolls or explosions of one die, unless the behaviour sets its own limit.
+pub const DEFAULT_LIMIT: usize = 100;
+
 impl Behaviour {
     pub fn apply(
         behaviour: Behaviour,
         dice: &Dice,
         values: Vec<Value>,
+        limit: usize,
         rng: &mut dyn RngCore,
-    ) -> Vec<Value> {
-        match behaviour {
+    ) -> Result<Vec<Value>> {
+        Ok(match behaviour {
             Self::Keep(number, direction) => {
                 Self::apply_discard(number, DiscardType::Keep(direction), values)
             }
             Self::Drop(number, direction) => {
                 Self::apply_discard(number, DiscardType::Drop(direction), values)
             }
-            Self::Reroll(point, repeat) => Self::apply_reroll(point, repeat, dice, values, rng),
-            Self::Explode(point, explode) => Self::apply_explode(point, explode, dice, values, rng),
+            Self::Reroll(point, reroll, own) => Self::apply_reroll(
+                point,
+                reroll,
+                false,
+                own.unwrap_or(limit),
+                dice,
+                values,
+                rng,
+            )?,
+            Self::RerollTens(point, reroll) => {
+                Self::apply_reroll(point, reroll, true, limit, dice, values, rng)?
+            }
+            Self::Bonus(number) => Self::apply_tens(number, DiscardDirection::Low, values, rng),
+            Self::Penalty(number) => Self::apply_tens(number, DiscardDirection::High, values, rng),
+            Self::Explode(point, explode, own) => {
+                Self::apply_explode(point, explode, own.unwrap_or(limit), dice, values, rng)?
+            }
             Self::Critical(failure, success) => {
                 Self::apply_critical(failure, success, dice, values)
             }
-        }
+        })
     }
 
     pub fn apply_all(
         behaviours: Vec<Behaviour>,
         dice: &Dice,
         values: Vec<Value>,
+        limit: usize,
         rng: &mut dyn RngCore,
-    ) -> Vec<Value> {
+    ) -> Result<Vec<Value>> {
         let mut values = values;
         let mut behaviours = behaviours;
         behaviours.sort_unstable();
         for behaviour in behaviours {
-            values = Self::apply(behaviour, dice, values, rng);
+            values = Self::apply(behaviour, dice, values, limit, rng)?;
         }
-        values
+        Ok(values)
     }
 
-    fn failure(dice: &Dice, point: Option<Bounded>) -> Option<Bounded> {
+    pub(crate) fn failure(dice: &Dice, point: Option<Bounded>) -> Option<Bounded> {
         point.or_else(|| dice.start())
     }
 
-    fn success(dice: &Dice, point: Option<Bounded>) -> Option<Bounded> {
+    pub(crate) fn success(dice: &Dice, point: Option<Bounded>) -> Option<Bounded> {
         point.or_else(|| dice.end())
     }
 
     fn apply_reroll(
         point: Option<Bounded>,
-        repeat: bool,
+        reroll: RerollType,
+        tens: bool,
+        limit: usize,
         dice: &Dice,
         values: Vec<Value>,
         rng: &mut dyn RngCore,
-    ) -> Vec<Value> {
+    ) -> Result<Vec<Value>> {
         if let Some(range) = Self::failure(dice, point) {
             let mut result = Vec::new();
             for value in values {
                 let mut v = value;
+                let mut count = 0;
                 while range.contains(&v.value()) {
-                    v = v.clone().update(dice.roll(rng), Action::Reroll(v.value()));
-                    if !repeat {
+                    if count == limit {
+                        v = v.add(Action::Capped);
+                        break;
+                    }
+                    count += 1;
+                    let current = v.value();
+                    let r = if tens {
+                        Dice::reroll_tens(current, rng)
+                    } else {
+                        dice.roll(rng)?
+                    };
+                    v = match reroll {
+                        RerollType::High if r <= current => v.add(Action::Unused(r)),
+                        RerollType::Low if r >= current => v.add(Action::Unused(r)),
+                        _ => v.update(r, Action::Reroll(current)),
+                    };
+                    if reroll != RerollType::Repeat {
                         break;
                     }
                 }
                 result.push(v);
             }
-            result
+            Ok(result)
         } else {
-            values
+            Ok(values)
+        }
+    }
+
+    /// Roll `number` extra tens dice for each value, keeping the tens die in `keep` direction and
+    /// marking the others as the bonus or penalty dice they were.
+    fn apply_tens(
+        number: usize,
+        keep: DiscardDirection,
+        values: Vec<Value>,
+        rng: &mut dyn RngCore,
+    ) -> Vec<Value> {
+        let mut result = Vec::new();
+        let tens_type = match keep {
+            DiscardDirection::Low => TensType::Bonus,
+            DiscardDirection::High => TensType::Penalty,
+        };
+        for value in values {
+            let mut kept = value.value();
+            let mut v = value;
+            for _ in 0..number {
+                let r = Dice::reroll_tens(kept, rng);
+                let better = match keep {
+                    DiscardDirection::Low => r < kept,
+                    DiscardDirection::High => r > kept,
+                };
+                if better {
+                    v = v.add(Action::Tens(kept, tens_type));
+                    kept = r;
+                } else {
+                    v = v.add(Action::Tens(r, tens_type));
+                }
+            }
+            let tens = kept % 100 / 10;
+            result.push(match keep {
+                DiscardDirection::Low => v.update(kept, Action::Bonus(tens)),
+                DiscardDirection::High => v.update(kept, Action::Penalty(tens)),
+            });
         }
+        result
     }
 
     fn apply_critical(
@@ -118,11 +214,17 @@ impl Behaviour {
         result
     }
 
-    fn apply_discard(number: usize, discard: DiscardType, values: Vec<Value>) -> Vec<Value> {
-        let mut numbers: Vec<i8> = values
+    pub(crate) fn apply_discard(
+        number: usize,
+        discard: DiscardType,
+        values: Vec<Value>,
+    ) -> Vec<Value> {
+        // an exploded die ranks by the total of every face it rolled
+        let total = |v: &Value| v.faces().iter().sum::<i32>();
+        let mut numbers: Vec<i32> = values
             .iter()
             .filter(|v| !v.actions().contains(&Action::Discard))
-            .map(|v| v.value())
+            .map(total)
             .collect();
         numbers.sort_unstable();
         if let DiscardType::Drop(DiscardDirection::High)
@@ -140,7 +242,7 @@ impl Behaviour {
         for value in values {
             results.push(if value.actions().contains(&Action::Discard) {
                 value
-            } else if let Ok(index) = discards.binary_search(&value.value()) {
+            } else if let Ok(index) = discards.binary_search(&total(&value)) {
                 discards.remove(index);
                 value.add(Action::Discard)
             } else {
@@ -153,18 +255,25 @@ impl Behaviour {
     fn apply_explode(
         point: Option<Bounded>,
         explode: ExType,
+        limit: usize,
         dice: &Dice,
         values: Vec<Value>,
         rng: &mut dyn RngCore,
-    ) -> Vec<Value> {
+    ) -> Result<Vec<Value>> {
         if let Some(range) = Self::success(dice, point) {
             let mut result = Vec::new();
             for value in values {
                 let mut first = true;
+                let mut count = 0;
                 let mut v = value;
                 let mut r = v.value();
                 while range.contains(&r) {
-                    r = dice.roll(rng);
+                    if count == limit {
+                        v = v.add(Action::Capped);
+                        break;
+                    }
+                    count += 1;
+                    r = dice.roll(rng)?;
                     v = match explode {
                         ExType::Standard => {
                             v.clone().update(r, Action::Explode(v.value(), explode))
@@ -177,80 +286,111 @@ impl Behaviour {
                             if first {
                                 v = v.clone().add(Action::Explode(v.value(), explode));
                             }
-                            v.clone().update(v.value() + r, Action::Explode(r, explode))
+                            let total = v.value().checked_add(r).ok_or_else(|| {
+                                anyhow!("Overflow compounding {} with {}", v.value(), r)
+                            })?;
+                            v.clone().update(total, Action::Explode(r, explode))
                         }
                     };
                     first = false;
                 }
                 result.push(v);
             }
-            result
+            Ok(result)
         } else {
-            values
+            Ok(values)
+        }
+    }
+
+    /// Split a trailing iteration limit, such as the `{3}` of `!6{3}`, from the point.
+    fn parse_limit(s: &str) -> Result<(&str, Option<usize>)> {
+        match s.strip_suffix('}').and_then(|s| s.rsplit_once('{')) {
+            Some((_, "0")) => Err(anyhow!("Limit in '{}' must be at least 1", s)),
+            Some((point, limit)) => Ok((point, Some(limit.parse()?))),
+            None => Ok((s, None)),
         }
     }
 
     fn parse_reroll(s: &str) -> Result<Behaviour> {
-        let point = if s.is_empty() {
-            None
-        } else {
-            Some(Bounded::range_to(s.parse()?))
+        let (s, limit) = Self::parse_limit(s)?;
+        let (s, tens) = match s.strip_prefix('%') {
+            Some(s) => (s, true),
+            None => (s, false),
+        };
+        let (point, reroll) = match s.get(..1).unwrap_or_default() {
+            "o" => (&s[1..], RerollType::Once),
+            "h" => (&s[1..], RerollType::High),
+            "l" => (&s[1..], RerollType::Low),
+            _ => (s, RerollType::Repeat),
         };
-        Ok(Behaviour::Reroll(point, true))
+        if limit.is_some() && reroll != RerollType::Repeat {
+            return Err(anyhow!("Only a repeating reroll takes a limit"));
+        }
+        let point = Bounded::parse_point(point, Bounded::range_to)?;
+        if tens && limit.is_some() {
+            Err(anyhow!("A tens reroll takes no limit"))
+        } else if tens {
+            Ok(Behaviour::RerollTens(point, reroll))
+        } else {
+            Ok(Behaviour::Reroll(point, reroll, limit))
+        }
     }
 
     fn parse_explode(s: &str) -> Result<Behaviour> {
+        let (s, limit) = Self::parse_limit(s)?;
         if s.is_empty() {
-            Ok(Behaviour::Explode(None, ExType::Standard))
+            Ok(Behaviour::Explode(None, ExType::Standard, limit))
         } else {
-            let (range, extype) = match &s[..1] {
+            let (range, extype) = match s.get(..1).unwrap_or_default() {
                 "!" | "c" => (
-                    if s[1..].is_empty() {
-                        None
-                    } else {
-                        Some(Bounded::range_from(s[1..].parse()?))
-                    },
+                    Bounded::parse_point(&s[1..], Bounded::range_from)?,
                     ExType::Compound,
                 ),
                 "p" => (
-                    if s[1..].is_empty() {
-                        None
-                    } else {
-                        Some(Bounded::range_from(s[1..].parse()?))
-                    },
+                    Bounded::parse_point(&s[1..], Bounded::range_from)?,
                     ExType::Penetrating,
                 ),
-                "" => (None, ExType::Standard),
-                _ => (Some(Bounded::range_from(s.parse()?)), ExType::Standard),
+                _ => (
+                    Bounded::parse_point(s, Bounded::range_from)?,
+                    ExType::Standard,
+                ),
             };
-            Ok(Behaviour::Explode(range, extype))
+            Ok(Behaviour::Explode(range, extype, limit))
         }
     }
 
     fn parse_critical(s: &str) -> Result<Behaviour> {
-        match &s[..1] {
+        match s.get(..1).unwrap_or_default() {
             "s" => Ok(Behaviour::Critical(
                 None,
-                if s[1..].is_empty() {
-                    None
-                } else {
-                    Some(Bounded::range_from(s[1..].parse()?))
-                },
+                Bounded::parse_point(&s[1..], Bounded::range_from)?,
             )),
             "f" => Ok(Behaviour::Critical(
-                if s[1..].is_empty() {
-                    None
-                } else {
-                    Some(Bounded::range_to(s[1..].parse()?))
-                },
+                Bounded::parse_point(&s[1..], Bounded::range_to)?,
                 None,
             )),
             _ => Err(anyhow!("Unable to parse Critical Behaviour '{}'", s)),
         }
     }
 
+    fn parse_tens(s: &str) -> Result<Behaviour> {
+        let (number, kind) = s.split_at(s.len().saturating_sub(1));
+        let number = match number {
+            "" => 1,
+            number => number.parse()?,
+        };
+        if number == 0 {
+            return Err(anyhow!("Bonus and penalty dice need a count of at least 1"));
+        }
+        match kind {
+            "b" => Ok(Behaviour::Bonus(number)),
+            "p" => Ok(Behaviour::Penalty(number)),
+            _ => Err(anyhow!("Unable to parse '+{}' as bonus or penalty dice", s)),
+        }
+    }
+
     fn parse_keep(s: &str) -> Result<Behaviour> {
-        let (number, direction) = match &s[..1] {
+        let (number, direction) = match s.get(..1).unwrap_or_default() {
             "h" => (s[1..].parse()?, DiscardDirection::High),
             "l" => (s[1..].parse()?, DiscardDirection::Low),
             _ => (s.parse()?, DiscardDirection::High),
@@ -259,7 +399,7 @@ impl Behaviour {
     }
 
     fn parse_drop(s: &str) -> Result<Behaviour> {
-        let (number, direction) = match &s[..1] {
+        let (number, direction) = match s.get(..1).unwrap_or_default() {
             "h" => (s[1..].parse()?, DiscardDirection::High),
             "l" => (s[1..].parse()?, DiscardDirection::Low),
             _ => (s.parse()?, DiscardDirection::Low),
@@ -272,8 +412,9 @@ impl FromStr for Behaviour {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Behaviour> {
-        match &s[..1] {
+        match s.get(..1).unwrap_or_default() {
             "r" => Ok(Self::parse_reroll(&s[1..])?),
+            "+" => Ok(Self::parse_tens(&s[1..])?),
             "!" | "x" => Ok(Self::parse_explode(&s[1..])?),
             "c" if s.len() > 1 => Ok(Self::parse_critical(&s[1..])?),
             "k" => Ok(Self::parse_keep(&s[1..])?),
@@ -283,6 +424,49 @@ impl FromStr for Behaviour {
     }
 }
 
+impl Display for Behaviour {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn low(point: &Option<Bounded>) -> String {
+            point
+                .map(|p| p.point(Bounded::range_to))
+                .unwrap_or_default()
+        }
+        fn high(point: &Option<Bounded>) -> String {
+            point
+                .map(|p| p.point(Bounded::range_from))
+                .unwrap_or_default()
+        }
+        fn limit(limit: &Option<usize>) -> String {
+            limit.map(|l| format!("{{{}}}", l)).unwrap_or_default()
+        }
+
+        match self {
+            Self::Reroll(p, RerollType::Repeat, l) => write!(f, "r{}{}", low(p), limit(l)),
+            Self::Reroll(p, RerollType::Once, l) => write!(f, "ro{}{}", low(p), limit(l)),
+            Self::Reroll(p, RerollType::High, l) => write!(f, "rh{}{}", low(p), limit(l)),
+            Self::Reroll(p, RerollType::Low, l) => write!(f, "rl{}{}", low(p), limit(l)),
+            Self::Bonus(1) => write!(f, "+b"),
+            Self::Bonus(n) => write!(f, "+{}b", n),
+            Self::Penalty(1) => write!(f, "+p"),
+            Self::Penalty(n) => write!(f, "+{}p", n),
+            Self::RerollTens(p, RerollType::Repeat) => write!(f, "r%{}", low(p)),
+            Self::RerollTens(p, RerollType::Once) => write!(f, "r%o{}", low(p)),
+            Self::RerollTens(p, RerollType::High) => write!(f, "r%h{}", low(p)),
+            Self::RerollTens(p, RerollType::Low) => write!(f, "r%l{}", low(p)),
+            Self::Explode(p, ExType::Standard, l) => write!(f, "!{}{}", high(p), limit(l)),
+            Self::Explode(p, ExType::Compound, l) => write!(f, "!!{}{}", high(p), limit(l)),
+            Self::Explode(p, ExType::Penetrating, l) => write!(f, "!p{}{}", high(p), limit(l)),
+            Self::Critical(None, s) => write!(f, "cs{}", high(s)),
+            Self::Critical(fail, None) => write!(f, "cf{}", low(fail)),
+            Self::Critical(fail, s) => write!(f, "cf{}cs{}", low(fail), high(s)),
+            Self::Keep(n, DiscardDirection::High) => write!(f, "k{}", n),
+            Self::Keep(n, DiscardDirection::Low) => write!(f, "kl{}", n),
+            Self::Drop(n, DiscardDirection::Low) => write!(f, "d{}", n),
+            Self::Drop(n, DiscardDirection::High) => write!(f, "dh{}", n),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use {
@@ -334,11 +518,19 @@ mod test {
     }
 
     #[test]
-    fn check_apply_reroll_once() {
+    fn check_apply_reroll_once() -> Result<()> {
         let mut rng = rng(Dice::D6, 1);
         let values = values(vec![1, 2, 3, 4, 5, 6]);
 
-        let result = Behaviour::apply_reroll(None, false, &Dice::D6, values, &mut rng);
+        let result = Behaviour::apply_reroll(
+            None,
+            RerollType::Once,
+            false,
+            DEFAULT_LIMIT,
+            &Dice::D6,
+            values,
+            &mut rng,
+        )?;
 
         assert_eq!(result.len(), 6);
         assert_eq!(result[0].value(), 2);
@@ -347,14 +539,24 @@ mod test {
             action(&result),
             vec![Some(Action::Reroll(1)), None, None, None, None, None,]
         );
+
+        Ok(())
     }
 
     #[test]
-    fn check_apply_reroll() {
+    fn check_apply_reroll() -> Result<()> {
         let mut rng = rng(Dice::D6, 0);
         let values = values(vec![1, 2, 3, 4, 5, 6]);
 
-        let result = Behaviour::apply_reroll(None, true, &Dice::D6, values, &mut rng);
+        let result = Behaviour::apply_reroll(
+            None,
+            RerollType::Repeat,
+            false,
+            DEFAULT_LIMIT,
+            &Dice::D6,
+            values,
+            &mut rng,
+        )?;
 
         assert_eq!(result.len(), 6);
         assert_eq!(result[0].value(), 2);
@@ -367,6 +569,55 @@ mod test {
         assert!(actions[3].is_empty());
         assert!(actions[4].is_empty());
         assert!(actions[5].is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_apply_reroll_keep() -> Result<()> {
+        let point = Some(Bounded::range_to(2));
+
+        let mut rng = seq_rng(Dice::D6, [3, 0].iter().cloned());
+        let result = Behaviour::apply_reroll(
+            point,
+            RerollType::High,
+            false,
+            DEFAULT_LIMIT,
+            &Dice::D6,
+            values(vec![1, 2, 5]),
+            &mut rng,
+        )?;
+
+        assert_eq!(
+            result.iter().map(|v| v.value()).collect::<Vec<_>>(),
+            vec![4, 2, 5]
+        );
+        assert_eq!(
+            action(&result),
+            vec![Some(Action::Reroll(1)), Some(Action::Unused(1)), None]
+        );
+
+        let mut rng = seq_rng(Dice::D6, [3, 0].iter().cloned());
+        let result = Behaviour::apply_reroll(
+            point,
+            RerollType::Low,
+            false,
+            DEFAULT_LIMIT,
+            &Dice::D6,
+            values(vec![1, 2, 5]),
+            &mut rng,
+        )?;
+
+        assert_eq!(
+            result.iter().map(|v| v.value()).collect::<Vec<_>>(),
+            vec![1, 1, 5]
+        );
+        assert_eq!(
+            action(&result),
+            vec![Some(Action::Unused(4)), Some(Action::Reroll(2)), None]
+        );
+
+        Ok(())
     }
 
     #[test]
@@ -445,6 +696,31 @@ mod test {
         );
     }
 
+    #[test]
+    fn check_apply_discard_exploded() {
+        // a 6 then 1 totals 7, beating the 5 despite its last face
+        let exploded = || {
+            vec![
+                Value::new(1).add(Action::Explode(6, ExType::Standard)),
+                Value::new(5),
+            ]
+        };
+
+        let kept =
+            Behaviour::apply_discard(1, DiscardType::Keep(DiscardDirection::High), exploded());
+        let dropped =
+            Behaviour::apply_discard(1, DiscardType::Drop(DiscardDirection::Low), exploded());
+
+        assert_eq!(actions(&dropped), actions(&kept));
+        assert_eq!(
+            actions(&kept),
+            vec![
+                vec![Action::Explode(6, ExType::Standard)],
+                vec![Action::Discard]
+            ]
+        );
+    }
+
     #[test]
     fn check_apply_discard_drop_high() {
         let values = values(vec![1, 2, 3, 4, 5, 6]);
@@ -486,11 +762,137 @@ mod test {
     }
 
     #[test]
-    fn check_apply_explode_standard() {
+    fn check_apply_reroll_tens() -> Result<()> {
+        let mut rng = seq_rng(Dice::D10, [2, 9].into_iter());
+        let result = Behaviour::apply(
+            Behaviour::RerollTens(Some(Bounded::range_from(50)), RerollType::Once),
+            &Dice::Percentile,
+            values(vec![87, 100, 15]),
+            DEFAULT_LIMIT,
+            &mut rng,
+        )?;
+
+        assert_eq!(
+            result.iter().map(|v| v.value()).collect::<Vec<_>>(),
+            vec![27, 90, 15]
+        );
+        assert_eq!(
+            action(&result),
+            vec![Some(Action::Reroll(87)), Some(Action::Reroll(100)), None]
+        );
+
+        let mut rng = seq_rng(Dice::D10, [6].into_iter());
+        let result = Behaviour::apply(
+            Behaviour::RerollTens(None, RerollType::Low),
+            &Dice::Percentile,
+            values(vec![3, 45]),
+            DEFAULT_LIMIT,
+            &mut rng,
+        )?;
+
+        assert_eq!(action(&result), vec![Some(Action::Unused(63)), None]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_apply_tens() -> Result<()> {
+        let mut rng = seq_rng(Dice::D10, [2, 9, 0].into_iter());
+        let result = Behaviour::apply(
+            Behaviour::Bonus(2),
+            &Dice::Percentile,
+            values(vec![47]),
+            DEFAULT_LIMIT,
+            &mut rng,
+        )?;
+
+        assert_eq!(result[0].value(), 27);
+        assert_eq!(
+            actions(&result),
+            vec![vec![
+                Action::Tens(47, TensType::Bonus),
+                Action::Tens(97, TensType::Bonus),
+                Action::Bonus(2)
+            ]]
+        );
+
+        let result = Behaviour::apply(
+            Behaviour::Penalty(1),
+            &Dice::D100,
+            values(vec![30, 65]),
+            DEFAULT_LIMIT,
+            &mut rng,
+        )?;
+
+        assert_eq!(
+            result.iter().map(|v| v.value()).collect::<Vec<_>>(),
+            vec![100, 65]
+        );
+        assert_eq!(
+            actions(&result),
+            vec![
+                vec![Action::Tens(30, TensType::Penalty), Action::Penalty(0)],
+                vec![Action::Tens(25, TensType::Penalty), Action::Penalty(6)]
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_apply_limit() -> Result<()> {
+        let mut ones = seq_rng(Dice::D6, [0].into_iter());
+        let result = Behaviour::apply_reroll(
+            None,
+            RerollType::Repeat,
+            false,
+            2,
+            &Dice::D6,
+            values(vec![1, 2]),
+            &mut ones,
+        )?;
+
+        assert_eq!(
+            actions(&result),
+            vec![
+                vec![Action::Reroll(1), Action::Reroll(1), Action::Capped],
+                vec![]
+            ]
+        );
+
+        let mut sixes = seq_rng(Dice::D6, [5].into_iter());
+        let result = Behaviour::apply(
+            Behaviour::Explode(None, ExType::Standard, Some(1)),
+            &Dice::D6,
+            values(vec![6, 2]),
+            DEFAULT_LIMIT,
+            &mut sixes,
+        )?;
+
+        assert_eq!(
+            actions(&result),
+            vec![
+                vec![Action::Explode(6, ExType::Standard), Action::Capped],
+                vec![]
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_apply_explode_standard() -> Result<()> {
         let mut rng = rng(Dice::D6, 5);
         let values = values(vec![1, 2, 3, 4, 5, 6]);
 
-        let result = Behaviour::apply_explode(None, ExType::Standard, &Dice::D6, values, &mut rng);
+        let result = Behaviour::apply_explode(
+            None,
+            ExType::Standard,
+            DEFAULT_LIMIT,
+            &Dice::D6,
+            values,
+            &mut rng,
+        )?;
 
         assert_eq!(result.len(), 6);
         assert_eq!(result[5].value(), 1);
@@ -509,14 +911,23 @@ mod test {
                 Action::Explode(6, ExType::Standard)
             ]
         );
+
+        Ok(())
     }
 
     #[test]
-    fn check_apply_explode_compound() {
+    fn check_apply_explode_compound() -> Result<()> {
         let mut rng = rng(Dice::D6, 5);
         let values = values(vec![1, 2, 3, 4, 5, 6]);
 
-        let result = Behaviour::apply_explode(None, ExType::Compound, &Dice::D6, values, &mut rng);
+        let result = Behaviour::apply_explode(
+            None,
+            ExType::Compound,
+            DEFAULT_LIMIT,
+            &Dice::D6,
+            values,
+            &mut rng,
+        )?;
 
         assert_eq!(result.len(), 6);
         assert_eq!(result[5].value(), 13);
@@ -535,15 +946,23 @@ mod test {
                 Action::Explode(1, ExType::Compound)
             ]
         );
+
+        Ok(())
     }
 
     #[test]
-    fn check_apply_explode_pentrating() {
+    fn check_apply_explode_pentrating() -> Result<()> {
         let mut rng = rng(Dice::D6, 5);
         let values = values(vec![1, 2, 3, 4, 5, 6]);
 
-        let result =
-            Behaviour::apply_explode(None, ExType::Penetrating, &Dice::D6, values, &mut rng);
+        let result = Behaviour::apply_explode(
+            None,
+            ExType::Penetrating,
+            DEFAULT_LIMIT,
+            &Dice::D6,
+            values,
+            &mut rng,
+        )?;
 
         assert_eq!(result.len(), 6);
         assert_eq!(result[5].value(), 5);
@@ -555,26 +974,46 @@ mod test {
         assert!(actions[3].is_empty());
         assert!(actions[4].is_empty());
         assert_eq!(actions[5], vec![Action::Explode(6, ExType::Penetrating)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_apply_explode_compound_overflow() {
+        let dice = Dice::Other(i32::MAX - 1, i32::MAX);
+        let mut rng = rng(dice.clone(), 1);
+        let values = values(vec![i32::MAX]);
+
+        let result = Behaviour::apply_explode(
+            None,
+            ExType::Compound,
+            DEFAULT_LIMIT,
+            &dice,
+            values,
+            &mut rng,
+        );
+
+        assert!(result.is_err());
     }
 
     #[test]
     fn check_behaviour_ordering() {
         let mut v = vec![
-            Behaviour::Reroll(Some(Bounded::from_range(..1)), true),
+            Behaviour::Reroll(Some(Bounded::from_range(..1)), RerollType::Repeat, None),
             Behaviour::Critical(None, None),
             Behaviour::Drop(1, DiscardDirection::Low),
-            Behaviour::Explode(None, ExType::Penetrating),
+            Behaviour::Explode(None, ExType::Penetrating, None),
             Behaviour::Keep(2, DiscardDirection::High),
-            Behaviour::Reroll(None, false),
+            Behaviour::Reroll(None, RerollType::Once, None),
         ];
         v.sort();
 
         assert_eq!(
             v,
             vec![
-                Behaviour::Reroll(None, false),
-                Behaviour::Reroll(Some(Bounded::from_range(..1)), true),
-                Behaviour::Explode(None, ExType::Penetrating),
+                Behaviour::Reroll(None, RerollType::Once, None),
+                Behaviour::Reroll(Some(Bounded::from_range(..1)), RerollType::Repeat, None),
+                Behaviour::Explode(None, ExType::Penetrating, None),
                 Behaviour::Critical(None, None),
                 Behaviour::Keep(2, DiscardDirection::High),
                 Behaviour::Drop(1, DiscardDirection::Low),
@@ -583,7 +1022,7 @@ mod test {
     }
 
     #[test]
-    fn check_apply() {
+    fn check_apply() -> Result<()> {
         let mut rng = rng(Dice::D6, 5);
         let values = values(vec![1, 2, 3, 4, 5, 6]);
 
@@ -591,8 +1030,9 @@ mod test {
             Behaviour::Drop(2, DiscardDirection::High),
             &Dice::D6,
             values,
+            DEFAULT_LIMIT,
             &mut rng,
-        );
+        )?;
 
         assert_eq!(
             action(&result),
@@ -605,10 +1045,12 @@ mod test {
                 Some(Action::Discard),
             ]
         );
+
+        Ok(())
     }
 
     #[test]
-    fn check_apply_all() {
+    fn check_apply_all() -> Result<()> {
         let mut rng = rng(Dice::D6, 5);
         let values = values(vec![1, 2, 3, 4, 5, 6]);
 
@@ -619,8 +1061,9 @@ mod test {
             ],
             &Dice::D6,
             values,
+            DEFAULT_LIMIT,
             &mut rng,
-        );
+        )?;
 
         assert_eq!(
             action(&result),
@@ -633,16 +1076,95 @@ mod test {
                 Some(Action::Discard),
             ]
         );
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_display() -> Result<()> {
+        for text in [
+            "r", "r2", "!", "!5", "!!", "!p6", "cs", "cs19", "cf2", "k3", "kl1", "d1", "dh2",
+            "r<3", "r=1", "r>=5", "ro", "ro1", "rh<3", "rl=6", "r{5}", "!{3}", "!!<=2{4}", "!>5",
+            "!!<=2", "!p4:5", "cs>18", "cf=1", "cs=-1", "r%<50", "r%o", "r%h>90", "r%l1:10", "+b",
+            "+3p",
+        ] {
+            assert_eq!(Behaviour::from_str(text)?.to_string(), text);
+        }
+
+        Ok(())
     }
 
     #[test]
     fn check_parse_reroll() -> Result<()> {
-        assert_eq!(Behaviour::from_str("r")?, Behaviour::Reroll(None, true));
+        assert_eq!(
+            Behaviour::from_str("r")?,
+            Behaviour::Reroll(None, RerollType::Repeat, None)
+        );
         assert_eq!(
             Behaviour::from_str("r2")?,
-            Behaviour::Reroll(Some(Bounded::from_range(..=2)), true)
+            Behaviour::Reroll(Some(Bounded::from_range(..=2)), RerollType::Repeat, None)
         );
-        assert!(matches!(Behaviour::from_str("rq"), Err(_)));
+        assert_eq!(
+            Behaviour::from_str("r<3")?,
+            Behaviour::Reroll(Some(Bounded::range_below(3)), RerollType::Repeat, None)
+        );
+        assert_eq!(
+            Behaviour::from_str("r1:2")?,
+            Behaviour::Reroll(Some(Bounded::range_between(1, 2)), RerollType::Repeat, None)
+        );
+        assert_eq!(
+            Behaviour::from_str("ro")?,
+            Behaviour::Reroll(None, RerollType::Once, None)
+        );
+        assert_eq!(
+            Behaviour::from_str("ro<3")?,
+            Behaviour::Reroll(Some(Bounded::range_below(3)), RerollType::Once, None)
+        );
+        assert_eq!(
+            Behaviour::from_str("rh2")?,
+            Behaviour::Reroll(Some(Bounded::from_range(..=2)), RerollType::High, None)
+        );
+        assert_eq!(
+            Behaviour::from_str("rl>=5")?,
+            Behaviour::Reroll(Some(Bounded::range_from(5)), RerollType::Low, None)
+        );
+        assert_eq!(
+            Behaviour::from_str("r%h>=50")?,
+            Behaviour::RerollTens(Some(Bounded::range_from(50)), RerollType::High)
+        );
+        assert_eq!(
+            Behaviour::from_str("r%")?,
+            Behaviour::RerollTens(None, RerollType::Repeat)
+        );
+        assert!(Behaviour::from_str("r%{2}").is_err());
+        assert_eq!(Behaviour::from_str("+b")?, Behaviour::Bonus(1));
+        assert_eq!(Behaviour::from_str("+2p")?, Behaviour::Penalty(2));
+        assert!(Behaviour::from_str("+0b").is_err());
+        assert!(Behaviour::from_str("+2").is_err());
+        assert!(Behaviour::from_str("rq").is_err());
+        assert!(Behaviour::from_str("roo").is_err());
+        assert!(Behaviour::from_str("r<").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_parse_limit() -> Result<()> {
+        assert_eq!(
+            Behaviour::from_str("!{3}")?,
+            Behaviour::Explode(None, ExType::Standard, Some(3))
+        );
+        assert_eq!(
+            Behaviour::from_str("!!>=5{10}")?,
+            Behaviour::Explode(Some(Bounded::range_from(5)), ExType::Compound, Some(10))
+        );
+        assert_eq!(
+            Behaviour::from_str("r<3{2}")?,
+            Behaviour::Reroll(Some(Bounded::range_below(3)), RerollType::Repeat, Some(2))
+        );
+        assert!(Behaviour::from_str("!{0}").is_err());
+        assert!(Behaviour::from_str("!{x}").is_err());
+        assert!(Behaviour::from_str("ro{2}").is_err());
 
         Ok(())
     }
@@ -652,35 +1174,35 @@ mod test {
         println!("!");
         assert_eq!(
             Behaviour::from_str("!")?,
-            Behaviour::Explode(None, ExType::Standard)
+            Behaviour::Explode(None, ExType::Standard, None)
         );
         println!("!2");
         assert_eq!(
             Behaviour::from_str("!2")?,
-            Behaviour::Explode(Some(Bounded::from_range(2..)), ExType::Standard)
+            Behaviour::Explode(Some(Bounded::from_range(2..)), ExType::Standard, None)
         );
         println!("!!");
         assert_eq!(
             Behaviour::from_str("!!")?,
-            Behaviour::Explode(None, ExType::Compound)
+            Behaviour::Explode(None, ExType::Compound, None)
         );
         println!("!!2");
         assert_eq!(
             Behaviour::from_str("!!2")?,
-            Behaviour::Explode(Some(Bounded::from_range(2..)), ExType::Compound)
+            Behaviour::Explode(Some(Bounded::from_range(2..)), ExType::Compound, None)
         );
         println!("!p");
         assert_eq!(
             Behaviour::from_str("!p")?,
-            Behaviour::Explode(None, ExType::Penetrating)
+            Behaviour::Explode(None, ExType::Penetrating, None)
         );
         println!("!p2");
         assert_eq!(
             Behaviour::from_str("!p2")?,
-            Behaviour::Explode(Some(Bounded::from_range(2..)), ExType::Penetrating)
+            Behaviour::Explode(Some(Bounded::from_range(2..)), ExType::Penetrating, None)
         );
         println!("!q");
-        // assert!(matches!(Behaviour::from_str("!q"), Err(_)));
+        // assert!(Behaviour::from_str("!q").is_err());
 
         Ok(())
     }
@@ -697,8 +1219,16 @@ mod test {
             Behaviour::from_str("cf2")?,
             Behaviour::Critical(Some(Bounded::from_range(..=2)), None)
         );
-        assert!(matches!(Behaviour::from_str("c"), Err(_)));
-        assert!(matches!(Behaviour::from_str("cq"), Err(_)));
+        assert_eq!(
+            Behaviour::from_str("cs>=19")?,
+            Behaviour::Critical(None, Some(Bounded::range_from(19)))
+        );
+        assert_eq!(
+            Behaviour::from_str("cf=1")?,
+            Behaviour::Critical(Some(Bounded::range_of(1)), None)
+        );
+        assert!(Behaviour::from_str("c").is_err());
+        assert!(Behaviour::from_str("cq").is_err());
 
         Ok(())
     }
@@ -717,7 +1247,7 @@ mod test {
             Behaviour::from_str("kl1")?,
             Behaviour::Keep(1, DiscardDirection::Low)
         );
-        assert!(matches!(Behaviour::from_str("kq"), Err(_)));
+        assert!(Behaviour::from_str("kq").is_err());
 
         Ok(())
     }
@@ -736,7 +1266,7 @@ mod test {
             Behaviour::from_str("dl1")?,
             Behaviour::Drop(1, DiscardDirection::Low)
         );
-        assert!(matches!(Behaviour::from_str("dq"), Err(_)));
+        assert!(Behaviour::from_str("dq").is_err());
 
         Ok(())
     }
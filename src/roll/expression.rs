@@ -0,0 +1,595 @@
+use {
+    crate::{
+        dice::{narrative::Symbols, Dice, Die},
+        roll::{
+            behaviour::{Behaviour, DEFAULT_LIMIT},
+            outcome::{Outcome, Outcomes},
+            validate::{validate, ValidationError},
+            wild::Wild,
+            Roll,
+        },
+    },
+    anyhow::{anyhow, Error, Result},
+    joinery::JoinableIterator,
+    rand::RngCore,
+    serde::{ser::SerializeStruct, Serialize, Serializer},
+    std::{
+        fmt::{self, Display},
+        str::FromStr,
+    },
+};
+
+#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone, Copy)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl Operator {
+    pub(crate) fn apply(&self, lhs: i32, rhs: i32) -> Result<i32> {
+        match self {
+            Self::Add => lhs.checked_add(rhs),
+            Self::Subtract => lhs.checked_sub(rhs),
+            Self::Multiply => lhs.checked_mul(rhs),
+            Self::Divide if rhs == 0 => return Err(anyhow!("Division by zero")),
+            Self::Divide => lhs.checked_div(rhs),
+        }
+        .ok_or_else(|| anyhow!("Overflow evaluating {} {} {}", lhs, self, rhs))
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Add | Self::Subtract => 1,
+            Self::Multiply | Self::Divide => 2,
+        }
+    }
+}
+
+impl FromStr for Operator {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Operator> {
+        match s {
+            "+" => Ok(Self::Add),
+            "-" => Ok(Self::Subtract),
+            "*" => Ok(Self::Multiply),
+            "/" => Ok(Self::Divide),
+            _ => Err(anyhow!("Unable to parse {} as Operator", s)),
+        }
+    }
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Add => "+",
+                Self::Subtract => "-",
+                Self::Multiply => "*",
+                Self::Divide => "/",
+            }
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expression {
+    Constant(i32),
+    Roll(Die, Vec<Behaviour>, Outcomes),
+    /// A trait roll, thrown as a group for the trait die and another for the wild die. It reads
+    /// as raises, so it only stands alone and is never an operand.
+    Wild(Wild),
+    Negate(Box<Expression>),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+}
+
+impl Expression {
+    pub fn binary(operator: Operator, lhs: Expression, rhs: Expression) -> Self {
+        Self::Binary(operator, Box::new(lhs), Box::new(rhs))
+    }
+
+    pub fn evaluate(&self, rng: &mut dyn RngCore) -> Result<Evaluation> {
+        self.evaluate_with_limit(DEFAULT_LIMIT, rng)
+    }
+
+    /// Evaluate, stopping any reroll or explosion without a limit of its own after `limit`
+    /// iterations of a die.
+    pub fn evaluate_with_limit(&self, limit: usize, rng: &mut dyn RngCore) -> Result<Evaluation> {
+        let mut rolls = Vec::new();
+        let total = self.calculate(limit, rng, &mut rolls)?;
+        // a lone roll group keeps its typed outcome, as does a pool of narrative dice whose
+        // symbols cancel across groups, anything else is an arithmetic total
+        let outcome = match (self, &rolls[..]) {
+            (Self::Roll(..), [roll]) => roll.outcome()?,
+            (Self::Wild(wild), _) => wild.outcome(&rolls),
+            _ if self.narrative() => {
+                let mut symbols = Symbols::default();
+                for roll in &rolls {
+                    if let Outcome::Narrative(rolled) = roll.outcome()? {
+                        symbols = symbols + rolled;
+                    }
+                }
+                Outcome::Narrative(symbols.cancel())
+            }
+            _ => Outcome::Total(total),
+        };
+        Ok(Evaluation {
+            expression: self.clone(),
+            rolls,
+            outcome,
+        })
+    }
+
+    fn calculate(&self, limit: usize, rng: &mut dyn RngCore, rolls: &mut Vec<Roll>) -> Result<i32> {
+        match self {
+            Self::Constant(value) => Ok(*value),
+            Self::Roll(die, behaviours, outcomes) => {
+                validate(die, behaviours, outcomes)?;
+                let mut roll = Roll::from_roll(die.clone(), rng)?.with_outcomes(*outcomes);
+                roll.apply(behaviours.clone(), limit, rng)?;
+                let total = roll.outcome()?.value();
+                rolls.push(roll);
+                Ok(total)
+            }
+            Self::Wild(wild) => {
+                wild.validate()?;
+                let thrown = wild.roll(limit, rng)?;
+                let total = wild.outcome(&thrown).value();
+                rolls.extend(thrown);
+                Ok(total)
+            }
+            Self::Negate(expression) => expression
+                .operand()?
+                .calculate(limit, rng, rolls)?
+                .checked_neg()
+                .ok_or_else(|| anyhow!("Overflow evaluating -{}", expression)),
+            Self::Binary(operator, lhs, rhs) => {
+                let lhs = lhs.operand()?.calculate(limit, rng, rolls)?;
+                let rhs = rhs.operand()?.calculate(limit, rng, rolls)?;
+                operator.apply(lhs, rhs)
+            }
+        }
+    }
+
+    /// Whether this only adds together groups of narrative dice.
+    fn narrative(&self) -> bool {
+        match self {
+            Self::Roll(die, _, _) => matches!(die.dice(), Dice::Symbolic(_)),
+            Self::Binary(Operator::Add, lhs, rhs) => lhs.narrative() && rhs.narrative(),
+            _ => false,
+        }
+    }
+
+    /// Check every roll group can be rolled, and every operand is a number, before any dice are
+    /// thrown.
+    pub fn validate(&self) -> std::result::Result<(), ValidationError> {
+        match self {
+            Self::Constant(_) => Ok(()),
+            Self::Roll(die, behaviours, outcomes) => validate(die, behaviours, outcomes),
+            Self::Wild(wild) => wild.validate(),
+            Self::Negate(expression) => expression.operand()?.validate(),
+            Self::Binary(_, lhs, rhs) => {
+                lhs.operand()?.validate()?;
+                rhs.operand()?.validate()
+            }
+        }
+    }
+
+    /// This expression as an operand of arithmetic, which a trait roll cannot be.
+    pub(crate) fn operand(&self) -> std::result::Result<&Self, ValidationError> {
+        match self {
+            Self::Wild(wild) => Err(ValidationError::WildArithmetic(wild.clone())),
+            _ => Ok(self),
+        }
+    }
+
+    /// Every roll group in the expression, from left to right.
+    pub fn groups(&self) -> Vec<&Expression> {
+        match self {
+            Self::Constant(_) => Vec::new(),
+            Self::Roll(..) | Self::Wild(_) => vec![self],
+            Self::Negate(expression) => expression.groups(),
+            Self::Binary(_, lhs, rhs) => {
+                let mut groups = lhs.groups();
+                groups.extend(rhs.groups());
+                groups
+            }
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Binary(operator, _, _) => operator.precedence(),
+            _ => u8::MAX,
+        }
+    }
+
+    /// Render the expression, substituting each roll group with the text produced by `roll`.
+    fn render<'a, F>(&'a self, roll: &mut F) -> String
+    where
+        F: FnMut(&'a Expression) -> String,
+    {
+        match self {
+            Self::Constant(value) => value.to_string(),
+            Self::Roll(..) | Self::Wild(_) => roll(self),
+            Self::Negate(expression) => {
+                format!("-{}", Self::group(expression, u8::MAX, roll))
+            }
+            Self::Binary(operator, lhs, rhs) => {
+                let precedence = operator.precedence();
+                let lhs = Self::group(lhs, precedence, roll);
+                // operators are left associative, so an equal precedence on the right needs grouping
+                let rhs = Self::group(rhs, precedence + 1, roll);
+                format!("{} {} {}", lhs, operator, rhs)
+            }
+        }
+    }
+
+    fn group<'a, F>(expression: &'a Expression, precedence: u8, roll: &mut F) -> String
+    where
+        F: FnMut(&'a Expression) -> String,
+    {
+        let text = expression.render(roll);
+        if expression.precedence() < precedence {
+            format!("({})", text)
+        } else {
+            text
+        }
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.render(&mut |group| match group {
+                Self::Roll(die, behaviours, outcomes) => {
+                    format!("{}{}{}", die, behaviours.iter().join_with(""), outcomes)
+                }
+                Self::Wild(wild) => wild.to_string(),
+                _ => String::new(),
+            })
+        )
+    }
+}
+
+/// The result of evaluating an expression: the dice thrown for each roll group and the outcome.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Evaluation {
+    expression: Expression,
+    rolls: Vec<Roll>,
+    outcome: Outcome,
+}
+
+impl Evaluation {
+    pub fn expression(&self) -> &Expression {
+        &self.expression
+    }
+
+    pub fn rolls(&self) -> &Vec<Roll> {
+        &self.rolls
+    }
+
+    pub fn total(&self) -> i32 {
+        self.outcome.value()
+    }
+
+    pub fn outcome(&self) -> Outcome {
+        self.outcome.clone()
+    }
+
+    fn text(&self) -> String {
+        let mut rolls = self.rolls.iter();
+        let mut next = || rolls.next().map(|r| r.to_string()).unwrap_or_default();
+        self.expression.render(&mut |group| match group {
+            // the trait die, then the wild die
+            Expression::Wild(_) => format!("[{} {}]", next(), next()),
+            _ => format!("[{}]", next()),
+        })
+    }
+}
+
+impl Serialize for Evaluation {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut evaluation = serializer.serialize_struct("Evaluation", 4)?;
+        evaluation.serialize_field("expression", &self.expression.to_string())?;
+        evaluation.serialize_field("rolls", &self.rolls)?;
+        evaluation.serialize_field("outcome", &self.outcome)?;
+        evaluation.serialize_field("total", &self.total())?;
+        evaluation.end()
+    }
+}
+
+impl Display for Evaluation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} => {}", self.text(), self.outcome())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::{
+            dice::{
+                bound::Bounded,
+                narrative::{Narrative, Symbol::*},
+            },
+            mock::rng::*,
+            roll::behaviour::DiscardDirection,
+        },
+    };
+
+    fn roll(dice: Dice, count: u32) -> Expression {
+        Expression::Roll(Die::new(dice, count), Vec::new(), Outcomes::Total)
+    }
+
+    #[test]
+    fn check_operator_apply() -> Result<()> {
+        assert_eq!(Operator::Add.apply(7, 2)?, 9);
+        assert_eq!(Operator::Subtract.apply(7, 2)?, 5);
+        assert_eq!(Operator::Multiply.apply(7, 2)?, 14);
+        assert_eq!(Operator::Divide.apply(7, 2)?, 3);
+
+        assert!(Operator::Divide.apply(7, 0).is_err());
+        assert!(Operator::Add.apply(i32::MAX, 1).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_evaluate_constants() -> Result<()> {
+        let mut rng = rng(Dice::D6, 0);
+        let expression = Expression::binary(
+            Operator::Add,
+            Expression::Constant(1),
+            Expression::binary(
+                Operator::Multiply,
+                Expression::Constant(2),
+                Expression::Constant(3),
+            ),
+        );
+
+        let evaluation = expression.evaluate(&mut rng)?;
+
+        assert_eq!(evaluation.total(), 7);
+        assert!(evaluation.rolls().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_evaluate_rolls() -> Result<()> {
+        let mut rng = rng(Dice::D6, 0);
+        let expression = Expression::binary(
+            Operator::Subtract,
+            Expression::binary(Operator::Add, roll(Dice::D6, 2), roll(Dice::D6, 1)),
+            Expression::Constant(1),
+        );
+
+        let evaluation = expression.evaluate(&mut rng)?;
+
+        assert_eq!(evaluation.rolls().len(), 2);
+        assert_eq!(evaluation.total(), 1 + 2 + 3 - 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_evaluate_behaviours() -> Result<()> {
+        let mut rng = rng(Dice::D6, 0);
+        let expression = Expression::Roll(
+            Die::new(Dice::D6, 4),
+            vec![Behaviour::Keep(3, DiscardDirection::High)],
+            Outcomes::Total,
+        );
+
+        let evaluation = expression.evaluate(&mut rng)?;
+
+        assert_eq!(evaluation.total(), 2 + 3 + 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_evaluate_outcome() -> Result<()> {
+        let mut rng = rng(Dice::D6, 0);
+        let expression = Expression::Roll(
+            Die::new(Dice::D6, 6),
+            Vec::new(),
+            Outcomes::Target(Bounded::range_from(5)),
+        );
+
+        let evaluation = expression.evaluate(&mut rng)?;
+
+        assert_eq!(evaluation.outcome(), Outcome::Target(2));
+        assert_eq!(evaluation.total(), 2);
+        assert_eq!(evaluation.to_string(), "[1 2 3 4 5 6] => 2 successes");
+
+        let expression = Expression::binary(Operator::Add, expression, Expression::Constant(1));
+
+        assert_eq!(expression.evaluate(&mut rng)?.outcome(), Outcome::Total(3));
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_evaluate_wild() -> Result<()> {
+        colored::control::set_override(false);
+        // the trait die rolls 5, the wild die 6 then 3 for 9
+        let mut rng = seq_rng(Dice::D6, [4, 5, 2].into_iter());
+        let expression = Expression::Wild(Wild::new(Dice::D6));
+
+        let evaluation = expression.evaluate(&mut rng)?;
+
+        assert_eq!(evaluation.rolls().len(), 2);
+        assert_eq!(evaluation.outcome(), Outcome::raises(9, 4, false));
+        assert_eq!(evaluation.total(), 2);
+        assert_eq!(
+            evaluation.to_string(),
+            "[5 9(6 3)] => 9, success with 1 raise"
+        );
+
+        let expression = Expression::binary(Operator::Add, expression, Expression::Constant(1));
+
+        assert_eq!(expression.groups().len(), 1);
+        assert_eq!(expression.to_string(), "sw(d6) + 1");
+        assert_eq!(
+            expression.validate(),
+            Err(ValidationError::WildArithmetic(Wild::new(Dice::D6)))
+        );
+        assert!(expression.evaluate(&mut rng).is_err());
+        assert!(
+            Expression::Negate(Box::new(Expression::Wild(Wild::new(Dice::D6))))
+                .evaluate(&mut rng)
+                .is_err()
+        );
+        assert!(Expression::Wild(Wild::new(Dice::from(Narrative::Boost)))
+            .evaluate(&mut rng)
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_evaluate_negate() -> Result<()> {
+        let mut rng = rng(Dice::D6, 0);
+        let expression = Expression::Negate(Box::new(roll(Dice::D6, 1)));
+
+        assert_eq!(expression.evaluate(&mut rng)?.total(), -1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_evaluate_division_by_zero() {
+        let mut rng = rng(Dice::D6, 0);
+        let expression = Expression::binary(
+            Operator::Divide,
+            Expression::Constant(1),
+            Expression::Constant(0),
+        );
+
+        assert!(expression.evaluate(&mut rng).is_err());
+    }
+
+    #[test]
+    fn check_evaluate_narrative() -> Result<()> {
+        colored::control::set_override(false);
+        let mut rng = rng(Dice::D8, 3);
+        let ability = roll(Dice::from(Narrative::Ability), 2);
+        let difficulty = roll(Dice::from(Narrative::Difficulty), 1);
+        let expression = Expression::binary(Operator::Add, ability, difficulty);
+
+        let evaluation = expression.evaluate(&mut rng)?;
+
+        assert_eq!(
+            evaluation.outcome(),
+            Outcome::Narrative(Symbols::of(&[Success, Success]))
+        );
+        assert_eq!(evaluation.total(), 2);
+        assert_eq!(evaluation.to_string(), "[ss a] + [t] => 2 successes");
+
+        let expression = Expression::binary(
+            Operator::Add,
+            roll(Dice::from(Narrative::Ability), 2),
+            Expression::Constant(1),
+        );
+
+        assert_eq!(expression.evaluate(&mut rng)?.outcome(), Outcome::Total(2));
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_evaluate_invalid() {
+        let mut rng = rng(Dice::D6, 0);
+        let keep = Expression::Roll(
+            Die::new(Dice::D6, 2),
+            vec![Behaviour::Keep(5, DiscardDirection::High)],
+            Outcomes::Total,
+        );
+        let expression = Expression::binary(Operator::Add, roll(Dice::D4, 1), keep);
+
+        assert_eq!(
+            expression.validate(),
+            Err(ValidationError::KeepTooMany(Die::new(Dice::D6, 2), 5, 2))
+        );
+        assert!(expression.evaluate(&mut rng).is_err());
+        assert!(roll(Dice::Other(5, 2), 1).evaluate(&mut rng).is_err());
+    }
+
+    #[test]
+    fn check_groups() {
+        let expression = Expression::binary(
+            Operator::Add,
+            Expression::Negate(Box::new(roll(Dice::D6, 2))),
+            Expression::binary(Operator::Add, Expression::Constant(1), roll(Dice::D4, 1)),
+        );
+
+        assert_eq!(
+            expression.groups(),
+            vec![&roll(Dice::D6, 2), &roll(Dice::D4, 1)]
+        );
+    }
+
+    #[test]
+    fn check_display() {
+        let expression = Expression::binary(
+            Operator::Multiply,
+            Expression::binary(Operator::Add, roll(Dice::D6, 2), Expression::Constant(1)),
+            Expression::binary(
+                Operator::Subtract,
+                Expression::Constant(3),
+                Expression::Constant(2),
+            ),
+        );
+
+        assert_eq!(expression.to_string(), "(2d6 + 1) * (3 - 2)");
+    }
+
+    #[test]
+    fn check_evaluation_display() -> Result<()> {
+        let mut rng = rng(Dice::D6, 0);
+        let expression = Expression::binary(Operator::Add, roll(Dice::D6, 2), roll(Dice::D4, 1));
+
+        let evaluation = expression.evaluate(&mut rng)?;
+
+        assert_eq!(evaluation.to_string(), "[1 2] + [2] => 5");
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_evaluation_serialize() -> Result<()> {
+        let mut rng = rng(Dice::D6, 0);
+        let expression = Expression::binary(
+            Operator::Add,
+            Expression::Roll(
+                Die::new(Dice::D6, 2),
+                vec![Behaviour::Keep(1, DiscardDirection::High)],
+                Outcomes::Total,
+            ),
+            Expression::Constant(1),
+        );
+
+        let evaluation = expression.evaluate(&mut rng)?;
+
+        assert_eq!(
+            serde_json::to_string(&evaluation)?,
+            concat!(
+                r#"{"expression":"2d6k1 + 1","rolls":[{"die":{"dice":"D6","count":2},"#,
+                r#""values":[{"value":1,"faces":[1],"discarded":true,"actions":["Discard"]},"#,
+                r#"{"value":2,"faces":[2],"discarded":false,"actions":[]}],"outcomes":"Total"}],"#,
+                r#""outcome":{"Total":3},"total":3}"#
+            )
+        );
+
+        Ok(())
+    }
+}
@@ -1,42 +1,63 @@
 #![allow(dead_code, unused_variables)]
 pub mod behaviour;
+pub mod expression;
 pub mod outcome;
+pub mod validate;
 pub mod value;
+pub mod wild;
 
 use {
     crate::{
-        dice::{Dice, Die},
-        roll::{behaviour::Behaviour, value::Value},
+        dice::{narrative::Symbols, Dice, Die},
+        roll::{
+            behaviour::{Behaviour, DiscardDirection, DiscardType},
+            outcome::{Outcome, Outcomes},
+            value::{Action, Value},
+        },
     },
+    anyhow::Result,
     joinery::{separators::Space, JoinableIterator},
     rand::RngCore,
+    serde::Serialize,
     std::fmt::{self, Display},
 };
 
-#[derive(Debug, PartialEq)]
-pub struct Roll<'a> {
-    die: &'a Die,
+/// The dice thrown for one roll group, after its behaviours have been applied.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Roll {
+    die: Die,
     values: Vec<Value>,
+    outcomes: Outcomes,
 }
 
-impl<'a> Roll<'a> {
-    pub fn new(die: &'a Die, values: Vec<i8>) -> Self {
+impl Roll {
+    pub fn new(die: Die, values: Vec<i32>) -> Self {
         Self {
             die,
             values: values.iter().map(|v| Value::new(*v)).collect(),
+            outcomes: Outcomes::default(),
         }
     }
 
-    pub fn from_roll(die: &'a Die, rng: &mut dyn RngCore) -> Self {
-        let values = die.roll(rng);
-        Self::new(die, values)
+    pub fn from_roll(die: Die, rng: &mut dyn RngCore) -> Result<Self> {
+        let values = die.roll(rng)?;
+        Ok(Self::new(die, values))
+    }
+
+    pub fn with_outcomes(mut self, outcomes: Outcomes) -> Self {
+        self.outcomes = outcomes;
+        self
+    }
+
+    pub fn die(&self) -> &Die {
+        &self.die
     }
 
     fn dice(&self) -> &Dice {
         self.die.dice()
     }
 
-    fn values(&self) -> &Vec<Value> {
+    pub fn values(&self) -> &Vec<Value> {
         &self.values
     }
 
@@ -48,14 +69,54 @@ impl<'a> Roll<'a> {
             .to_string()
     }
 
-    pub fn apply(&mut self, behaviours: Vec<Behaviour>, rng: &mut dyn RngCore) -> &Self {
-        self.values = Behaviour::apply_all(behaviours, self.dice(), self.values.clone(), rng);
+    /// Symbol dice always resolve to their cancelled symbols, validation having refused any
+    /// other outcome for them.
+    pub fn outcome(&self) -> Result<Outcome> {
+        match self.dice() {
+            Dice::Symbolic(symbolic) => Ok(Outcome::Narrative(
+                self.values
+                    .iter()
+                    .filter(|v| !v.actions().contains(&Action::Discard))
+                    .flat_map(|v| v.faces())
+                    .map(|v| symbolic.symbols(v))
+                    .sum::<Symbols>()
+                    .cancel(),
+            )),
+            _ => self.outcomes.process(&self.values),
+        }
+    }
+
+    pub fn apply(
+        &mut self,
+        behaviours: Vec<Behaviour>,
+        limit: usize,
+        rng: &mut dyn RngCore,
+    ) -> Result<&Self> {
+        let values =
+            Behaviour::apply_all(behaviours, self.dice(), self.values.clone(), limit, rng)?;
+        self.values = self.outcomes.mark(values);
 
-        self
+        Ok(self)
+    }
+
+    /// Keep the `number` highest values across several groups, as if their dice were thrown
+    /// together, discarding the rest.
+    pub(crate) fn keep_across(rolls: &mut [Roll], number: usize) {
+        let values = rolls.iter().flat_map(|r| r.values.clone()).collect();
+        let mut kept =
+            Behaviour::apply_discard(number, DiscardType::Keep(DiscardDirection::High), values)
+                .into_iter();
+        for roll in rolls {
+            roll.values = kept.by_ref().take(roll.values.len()).collect();
+        }
+    }
+
+    pub(crate) fn add(&mut self, action: Action) {
+        self.values = self.values.drain(..).map(|v| v.add(action)).collect();
     }
 }
 
-impl<'a> Display for Roll<'_> {
+impl Display for Roll {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.text())
     }
@@ -1,63 +1,886 @@
 use {
-    crate::roll::value::{Action, Value},
-    std::{collections::HashMap, iter::Iterator},
+    crate::{
+        dice::{bound::Bounded, narrative::Symbols},
+        roll::value::{Action, Value},
+    },
+    anyhow::{anyhow, Error, Result},
+    joinery::JoinableIterator,
+    serde::Serialize,
+    std::{
+        collections::BTreeMap,
+        fmt::{self, Display},
+        iter::Iterator,
+        ops::RangeBounds,
+        str::FromStr,
+    },
 };
 
-enum Outcomes {
+#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone, Copy, Default, Serialize)]
+pub enum Outcomes {
+    #[default]
     Total,
-    Target(i8),
+    Target(Bounded),
     Match,
+    /// Roll under a skill value, as `<=65`, for a single percentile die.
+    Skill(i32),
+    /// Successes on the target, less any failures, with an optional exceptional threshold,
+    /// as `t8f1e5`.
+    Pool(Bounded, Option<Bounded>, Option<i32>),
+    /// Hits on 5 and 6, glitching when more than half the dice show 1, as `h`.
+    Hits,
+}
+
+/// Matching dice grouped into sets of width (how many) by height (which face), widest and then
+/// highest first, with the unmatched waste dice in ascending order.
+#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone, Default, Serialize)]
+pub struct Sets {
+    sets: Vec<(u32, i32)>,
+    waste: Vec<i32>,
+}
+
+impl Sets {
+    pub fn of(faces: impl Iterator<Item = i32>) -> Self {
+        let counts = faces.fold(BTreeMap::new(), |mut m, v| {
+            *m.entry(v).or_insert(0u32) += 1;
+            m
+        });
+        let mut result = Self::default();
+        for (face, width) in counts {
+            if width > 1 {
+                result.sets.push((width, face));
+            } else {
+                result.waste.push(face);
+            }
+        }
+        result.sets.sort_unstable_by(|a, b| b.cmp(a));
+        result
+    }
+
+    pub fn sets(&self) -> &[(u32, i32)] {
+        &self.sets
+    }
+
+    pub fn waste(&self) -> &[i32] {
+        &self.waste
+    }
+
+    /// Position of the set made of `face`, if it matched any other.
+    pub fn index(&self, face: i32) -> Option<usize> {
+        self.sets.iter().position(|(_, height)| *height == face)
+    }
+}
+
+impl Display for Sets {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.sets.is_empty() {
+            write!(f, "no sets")?;
+        } else {
+            write!(
+                f,
+                "{}",
+                self.sets
+                    .iter()
+                    .map(|(width, height)| format!("{}x{}", width, height))
+                    .join_with(", ")
+            )?;
+        }
+        if !self.waste.is_empty() {
+            write!(f, ", waste {}", self.waste.iter().join_with(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Successes needed for an exceptional success when a pool does not set its own.
+pub const EXCEPTIONAL: i32 = 5;
+
+/// How a success pool went, from worst to best.
+#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone, Copy, Serialize)]
+pub enum Grade {
+    Botch,
+    Failure,
+    Success,
+    Exceptional,
+}
+
+/// Points over the target number needed for each raise on a trait roll.
+pub const RAISE: i32 = 4;
+
+/// How a trait roll went against its target number, with the raises earned by a success.
+#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone, Copy, Serialize)]
+pub enum Raise {
+    CriticalFailure,
+    Failure,
+    Success(i32),
+}
+
+/// How well a percentile roll went against a skill, from worst to best.
+#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone, Copy, Serialize)]
+pub enum Level {
+    Fumble,
+    Failure,
+    Regular,
+    Hard,
+    Extreme,
+    Critical,
+}
+
+impl Level {
+    /// A roll of 01 is critical; a fumble is 100, or 96 and over when the skill is below 50.
+    pub fn of(roll: i32, skill: i32) -> Self {
+        let fumble = if skill < 50 { 96 } else { 100 };
+        if roll == 1 {
+            Self::Critical
+        } else if roll >= fumble {
+            Self::Fumble
+        } else if roll <= skill / 5 {
+            Self::Extreme
+        } else if roll <= skill / 2 {
+            Self::Hard
+        } else if roll <= skill {
+            Self::Regular
+        } else {
+            Self::Failure
+        }
+    }
+
+    /// Degrees of success, zero for a failure and negative for a fumble.
+    pub fn value(&self) -> i32 {
+        match self {
+            Self::Fumble => -1,
+            Self::Failure => 0,
+            Self::Regular => 1,
+            Self::Hard => 2,
+            Self::Extreme => 3,
+            Self::Critical => 4,
+        }
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Fumble => "fumble",
+                Self::Failure => "failure",
+                Self::Regular => "regular success",
+                Self::Hard => "hard success",
+                Self::Extreme => "extreme success",
+                Self::Critical => "critical success",
+            }
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone, Serialize)]
+pub enum Outcome {
+    Total(i32),
+    Target(i32),
+    Match(Sets),
+    Narrative(Symbols),
+    Skill(Level),
+    Pool(i32, Grade),
+    /// Hits and whether the roll glitched, a glitch without hits being critical.
+    Hits(i32, bool),
+    /// Total kept from a trait roll and how it went against the target number.
+    Trait(i32, Raise),
 }
 
 impl Outcomes {
-    pub fn process(&self, values: Vec<Value>) -> i8 {
+    /// Extend a target into a success pool, or a pool already extended, counting failures at
+    /// `failure`.
+    pub fn with_failure(self, failure: Bounded) -> Result<Self> {
         match self {
-            Self::Total => values
-                .iter()
-                .filter(|v| !v.actions().contains(&Action::Discard))
-                .map(|v| v.value())
-                .sum(),
-            Self::Target(point) => values
-                .iter()
-                .filter(|v| !v.actions().contains(&Action::Discard))
-                .filter(|v| v.value() >= *point)
-                .count() as i8,
-            Self::Match => values
-                .iter()
-                .filter(|v| !v.actions().contains(&Action::Discard))
-                .fold(HashMap::new(), |mut m, v| {
-                    *m.entry(v.value()).or_insert(0) += 1i8;
-                    m
+            Self::Target(target) => Ok(Self::Pool(target, Some(failure), None)),
+            Self::Pool(target, _, exceptional) => {
+                Ok(Self::Pool(target, Some(failure), exceptional))
+            }
+            _ => Err(anyhow!("Failures need a target to count successes against")),
+        }
+    }
+
+    /// Extend a target into a success pool, or a pool already extended, that is exceptional at
+    /// `exceptional` successes.
+    pub fn with_exceptional(self, exceptional: i32) -> Result<Self> {
+        match self {
+            Self::Target(target) => Ok(Self::Pool(target, None, Some(exceptional))),
+            Self::Pool(target, failure, _) => Ok(Self::Pool(target, failure, Some(exceptional))),
+            _ => Err(anyhow!(
+                "An exceptional success needs a target to count successes against"
+            )),
+        }
+    }
+
+    /// The failures of a success pool, a bare number counting that face and below.
+    pub fn failure(s: &str) -> Result<Bounded> {
+        Bounded::parse_point(s, Bounded::range_to)?.ok_or_else(|| anyhow!("Failures need a point"))
+    }
+
+    /// Mark the dice an outcome reads, so that matched sets, hits and the ones behind a glitch
+    /// stand out.
+    pub fn mark(&self, values: Vec<Value>) -> Vec<Value> {
+        match self {
+            // an exploded die joins a set for each of its faces, as `process` counts them
+            Self::Match => {
+                let sets = Sets::of(Self::kept(&values).flat_map(|v| v.faces()));
+                values
+                    .into_iter()
+                    .map(|v| {
+                        if v.actions().contains(&Action::Discard) {
+                            return v;
+                        }
+                        let mut indexes: Vec<usize> = v
+                            .faces()
+                            .into_iter()
+                            .filter_map(|face| sets.index(face))
+                            .collect();
+                        indexes.sort_unstable();
+                        indexes.dedup();
+                        indexes
+                            .into_iter()
+                            .fold(v, |v, index| v.add(Action::Set(index as u32)))
+                    })
+                    .collect()
+            }
+            // an exploded die counts each of its faces, as `process` does
+            Self::Hits => values
+                .into_iter()
+                .map(|v| {
+                    if v.actions().contains(&Action::Discard) {
+                        return v;
+                    }
+                    let faces = v.faces();
+                    let v = if faces.iter().any(|face| Self::hit(*face)) {
+                        v.add(Action::Hit)
+                    } else {
+                        v
+                    };
+                    if faces.contains(&1) {
+                        v.add(Action::Glitch)
+                    } else {
+                        v
+                    }
                 })
-                .values()
-                .filter(|v| *v > &1i8)
-                .count() as i8,
+                .collect(),
+            _ => values,
+        }
+    }
+
+    pub(crate) fn hit(value: i32) -> bool {
+        value >= 5
+    }
+
+    fn kept(values: &[Value]) -> impl Iterator<Item = &Value> {
+        values
+            .iter()
+            .filter(|v| !v.actions().contains(&Action::Discard))
+    }
+
+    pub fn process(&self, values: &[Value]) -> Result<Outcome> {
+        let mut faces = Self::kept(values).flat_map(|v| v.faces());
+        Ok(match self {
+            Self::Total => Outcome::Total(
+                faces
+                    .try_fold(0i32, |total, v| total.checked_add(v))
+                    .ok_or_else(|| anyhow!("Overflow calculating total"))?,
+            ),
+            Self::Target(point) => {
+                Outcome::Target(Self::count(faces.filter(|v| point.contains(v)))?)
+            }
+            Self::Match => Outcome::Match(Sets::of(faces)),
+            Self::Pool(target, failure, exceptional) => {
+                let faces: Vec<i32> = faces.collect();
+                let successes = Self::count(faces.iter().filter(|v| target.contains(*v)))?;
+                let failures = match failure {
+                    Some(failure) => Self::count(faces.iter().filter(|v| failure.contains(*v)))?,
+                    None => 0,
+                };
+                Outcome::pool(successes, failures, *exceptional)
+            }
+            Self::Hits => {
+                let faces: Vec<i32> = faces.collect();
+                let hits = Self::count(faces.iter().filter(|v| Self::hit(**v)))?;
+                let ones = faces.iter().filter(|v| **v == 1).count();
+                Outcome::Hits(hits, ones * 2 > faces.len())
+            }
+            Self::Skill(skill) => match faces.collect::<Vec<_>>()[..] {
+                [roll] => Outcome::Skill(Level::of(roll, *skill)),
+                ref rolls => {
+                    return Err(anyhow!(
+                        "A skill roll needs a single die, not {}",
+                        rolls.len()
+                    ))
+                }
+            },
+        })
+    }
+
+    fn count<T>(iter: impl Iterator<Item = T>) -> Result<i32> {
+        Ok(i32::try_from(iter.count())?)
+    }
+}
+
+impl FromStr for Outcomes {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Outcomes> {
+        match s.get(..1) {
+            None => Ok(Self::Total),
+            Some("t") => {
+                let (point, pool) = s.split_at(s.find(['f', 'e']).unwrap_or(s.len()));
+                let target = match Bounded::parse_point(&point[1..], Bounded::range_from)? {
+                    Some(point) => point,
+                    None => return Err(anyhow!("Target {} needs a point", s)),
+                };
+                let (failure, exceptional) = pool.split_at(pool.find('e').unwrap_or(pool.len()));
+                let mut outcomes = Self::Target(target);
+                if let Some(point) = failure.strip_prefix('f') {
+                    outcomes = outcomes.with_failure(Self::failure(point)?)?;
+                } else if !failure.is_empty() {
+                    return Err(anyhow!("Unable to parse {} as a success pool", s));
+                }
+                if let Some(exceptional) = exceptional.strip_prefix('e') {
+                    outcomes = outcomes.with_exceptional(exceptional.parse()?)?;
+                }
+                Ok(outcomes)
+            }
+            Some("m") if s.len() == 1 => Ok(Self::Match),
+            Some("h") if s.len() == 1 => Ok(Self::Hits),
+            Some("<") => match s.strip_prefix("<=") {
+                Some(skill) => Ok(Self::Skill(skill.parse()?)),
+                None => Err(anyhow!("Skill {} must be rolled under with `<=`", s)),
+            },
+            _ => Err(anyhow!("Unable to parse {} as Outcome", s)),
+        }
+    }
+}
+
+impl Display for Outcomes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Total => Ok(()),
+            Self::Target(point) => write!(f, "t{}", point.point(Bounded::range_from)),
+            Self::Match => write!(f, "m"),
+            Self::Skill(skill) => write!(f, "<={}", skill),
+            Self::Hits => write!(f, "h"),
+            Self::Pool(target, failure, exceptional) => {
+                write!(f, "t{}", target.point(Bounded::range_from))?;
+                if let Some(failure) = failure {
+                    write!(f, "f{}", failure.point(Bounded::range_to))?;
+                }
+                if let Some(exceptional) = exceptional {
+                    write!(f, "e{}", exceptional)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Outcome {
+    /// Failures cancel successes; a pool with failures but no successes at all is a botch.
+    pub fn pool(successes: i32, failures: i32, exceptional: Option<i32>) -> Self {
+        let net = (successes - failures).max(0);
+        let grade = if successes == 0 && failures > 0 {
+            Grade::Botch
+        } else if net == 0 {
+            Grade::Failure
+        } else if net >= exceptional.unwrap_or(EXCEPTIONAL) {
+            Grade::Exceptional
+        } else {
+            Grade::Success
+        };
+        Self::Pool(net, grade)
+    }
+
+    /// Trait roll keeping `total`, a critical failure on snake eyes whatever the total.
+    pub fn raises(total: i32, target: i32, snake_eyes: bool) -> Self {
+        let raise = if snake_eyes {
+            Raise::CriticalFailure
+        } else if total < target {
+            Raise::Failure
+        } else {
+            Raise::Success((total - target) / RAISE)
+        };
+        Self::Trait(total, raise)
+    }
+
+    pub fn value(&self) -> i32 {
+        match self {
+            Self::Total(value) | Self::Target(value) => *value,
+            Self::Match(sets) => sets.sets().len() as i32,
+            Self::Narrative(symbols) => symbols.net(),
+            Self::Skill(level) => level.value(),
+            Self::Hits(hits, _) => *hits,
+            Self::Pool(_, Grade::Botch) => -1,
+            Self::Pool(net, _) => *net,
+            Self::Trait(_, Raise::CriticalFailure) => -1,
+            Self::Trait(_, Raise::Failure) => 0,
+            Self::Trait(_, Raise::Success(raises)) => raises + 1,
+        }
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Total(value) => write!(f, "{}", value),
+            Self::Target(1) => write!(f, "1 success"),
+            Self::Target(value) => write!(f, "{} successes", value),
+            Self::Match(sets) => write!(f, "{}", sets),
+            Self::Narrative(symbols) => write!(f, "{}", symbols),
+            Self::Skill(level) => write!(f, "{}", level),
+            Self::Hits(0, true) => write!(f, "critical glitch"),
+            Self::Hits(1, glitch) => write!(f, "1 hit{}", if *glitch { ", glitch" } else { "" }),
+            Self::Hits(hits, glitch) => {
+                write!(f, "{} hits{}", hits, if *glitch { ", glitch" } else { "" })
+            }
+            Self::Pool(_, Grade::Botch) => write!(f, "botch"),
+            Self::Pool(_, Grade::Failure) => write!(f, "failure"),
+            Self::Pool(1, Grade::Success) => write!(f, "1 success"),
+            Self::Pool(net, Grade::Success) => write!(f, "{} successes", net),
+            Self::Pool(net, Grade::Exceptional) => {
+                write!(f, "{} successes, exceptional", net)
+            }
+            Self::Trait(_, Raise::CriticalFailure) => write!(f, "critical failure"),
+            Self::Trait(total, Raise::Failure) => write!(f, "{}, failure", total),
+            Self::Trait(total, Raise::Success(0)) => write!(f, "{}, success", total),
+            Self::Trait(total, Raise::Success(1)) => write!(f, "{}, success with 1 raise", total),
+            Self::Trait(total, Raise::Success(raises)) => {
+                write!(f, "{}, success with {} raises", total, raises)
+            }
         }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use {super::*, crate::roll::value::test::*};
+    use {
+        super::*,
+        crate::roll::value::{test::*, ExType},
+    };
 
     #[test]
-    fn check_process_total() {
+    fn check_process_total() -> Result<()> {
         let values = values(vec![1, 2, 2, 3, 3, 3]);
 
-        assert_eq!(Outcomes::Total.process(values), 14);
+        assert_eq!(Outcomes::Total.process(&values)?, Outcome::Total(14));
+
+        Ok(())
     }
 
     #[test]
-    fn check_process_target() {
+    fn check_process_target() -> Result<()> {
         let values = values(vec![1, 2, 2, 3, 3, 3]);
 
-        assert_eq!(Outcomes::Target(3).process(values), 3);
+        assert_eq!(
+            Outcomes::Target(Bounded::range_from(3)).process(&values)?,
+            Outcome::Target(3)
+        );
+        assert_eq!(
+            Outcomes::Target(Bounded::range_of(2)).process(&values)?,
+            Outcome::Target(2)
+        );
+        assert_eq!(
+            Outcomes::Target(Bounded::range_below(3)).process(&values)?,
+            Outcome::Target(3)
+        );
+
+        Ok(())
     }
 
     #[test]
-    fn check_process_match() {
+    fn check_process_match() -> Result<()> {
         let values = values(vec![1, 2, 2, 3, 3, 3]);
 
-        assert_eq!(Outcomes::Match.process(values), 2);
+        let matched = Outcomes::Match.process(&values)?;
+
+        assert_eq!(matched.value(), 2);
+        assert_eq!(matched.to_string(), "3x3, 2x2, waste 1");
+
+        let outcome =
+            Outcomes::Match.process(&super::test::values(vec![7, 2, 7, 9, 2, 10, 7, 4, 4, 4]))?;
+
+        match outcome {
+            Outcome::Match(sets) => {
+                assert_eq!(sets.sets(), &[(3, 7), (3, 4), (2, 2)]);
+                assert_eq!(sets.waste(), &[9, 10]);
+                assert_eq!(sets.index(4), Some(1));
+                assert_eq!(sets.index(9), None);
+            }
+            outcome => panic!("expected sets, not {:?}", outcome),
+        }
+
+        assert_eq!(
+            Outcomes::Match
+                .process(&super::test::values(vec![1, 2, 3]))?
+                .to_string(),
+            "no sets, waste 1 2 3"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_process_discard() -> Result<()> {
+        let mut values = values(vec![1, 2, 2, 3, 3, 3]);
+        values[5] = values[5].clone().add(Action::Discard);
+
+        assert_eq!(Outcomes::Total.process(&values)?, Outcome::Total(11));
+        assert_eq!(
+            Outcomes::Target(Bounded::range_from(3)).process(&values)?,
+            Outcome::Target(2)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_process_explode() -> Result<()> {
+        let values = vec![
+            Value::new(1),
+            Value::new(4)
+                .add(Action::Explode(6, ExType::Standard))
+                .add(Action::Explode(6, ExType::Standard)),
+            Value::new(9)
+                .add(Action::Explode(6, ExType::Compound))
+                .add(Action::Explode(3, ExType::Compound)),
+        ];
+
+        assert_eq!(Outcomes::Total.process(&values)?, Outcome::Total(26));
+        assert_eq!(
+            Outcomes::Target(Bounded::range_from(6)).process(&values)?,
+            Outcome::Target(3)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_process_skill() -> Result<()> {
+        let skill = Outcomes::Skill(65);
+
+        assert_eq!(
+            skill.process(&values(vec![1]))?,
+            Outcome::Skill(Level::Critical)
+        );
+        assert_eq!(
+            skill.process(&values(vec![13]))?,
+            Outcome::Skill(Level::Extreme)
+        );
+        assert_eq!(
+            skill.process(&values(vec![32]))?,
+            Outcome::Skill(Level::Hard)
+        );
+        assert_eq!(
+            skill.process(&values(vec![65]))?,
+            Outcome::Skill(Level::Regular)
+        );
+        assert_eq!(
+            skill.process(&values(vec![99]))?,
+            Outcome::Skill(Level::Failure)
+        );
+        assert_eq!(
+            skill.process(&values(vec![100]))?,
+            Outcome::Skill(Level::Fumble)
+        );
+        assert_eq!(
+            Outcomes::Skill(40).process(&values(vec![96]))?,
+            Outcome::Skill(Level::Fumble)
+        );
+
+        let mut values = values(vec![80, 20]);
+        values[0] = values[0].clone().add(Action::Discard);
+
+        assert_eq!(skill.process(&values)?, Outcome::Skill(Level::Hard));
+        assert!(skill
+            .process(&[values[1].clone(), values[1].clone()])
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_process_pool() -> Result<()> {
+        let pool = Outcomes::Pool(Bounded::range_from(8), Some(Bounded::range_to(1)), None);
+
+        assert_eq!(
+            pool.process(&values(vec![8, 10, 1, 4]))?,
+            Outcome::Pool(1, Grade::Success)
+        );
+        assert_eq!(
+            pool.process(&values(vec![8, 1, 1]))?,
+            Outcome::Pool(0, Grade::Failure)
+        );
+        assert_eq!(
+            pool.process(&values(vec![3, 1, 5]))?,
+            Outcome::Pool(0, Grade::Botch)
+        );
+        assert_eq!(
+            pool.process(&values(vec![3, 5]))?,
+            Outcome::Pool(0, Grade::Failure)
+        );
+        assert_eq!(
+            pool.process(&values(vec![8, 9, 9, 10, 10, 1]))?,
+            Outcome::Pool(4, Grade::Success)
+        );
+        assert_eq!(
+            Outcomes::Pool(Bounded::range_from(8), None, Some(3))
+                .process(&values(vec![8, 9, 10, 1]))?,
+            Outcome::Pool(3, Grade::Exceptional)
+        );
+
+        let exploded = vec![Value::new(8).add(Action::Explode(10, ExType::Standard))];
+
+        assert_eq!(pool.process(&exploded)?, Outcome::Pool(2, Grade::Success));
+        assert_eq!(Outcome::Pool(0, Grade::Botch).value(), -1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_process_hits() -> Result<()> {
+        assert_eq!(
+            Outcomes::Hits.process(&values(vec![5, 6, 2, 1, 3]))?,
+            Outcome::Hits(2, false)
+        );
+        assert_eq!(
+            Outcomes::Hits.process(&values(vec![1, 1, 5, 1]))?,
+            Outcome::Hits(1, true)
+        );
+        assert_eq!(
+            Outcomes::Hits.process(&values(vec![1, 1, 4, 2]))?,
+            Outcome::Hits(0, false)
+        );
+        assert_eq!(
+            Outcomes::Hits.process(&values(vec![1, 1, 4]))?,
+            Outcome::Hits(0, true)
+        );
+
+        let edge = vec![
+            Value::new(1).add(Action::Explode(6, ExType::Standard)),
+            Value::new(1),
+        ];
+
+        assert_eq!(Outcomes::Hits.process(&edge)?, Outcome::Hits(1, true));
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_mark_sets() {
+        let mut values = values(vec![2, 5, 2, 5, 5, 9]);
+        values[5] = values[5].clone().add(Action::Discard);
+
+        assert_eq!(
+            actions(&Outcomes::Match.mark(values)),
+            vec![
+                vec![Action::Set(1)],
+                vec![Action::Set(0)],
+                vec![Action::Set(1)],
+                vec![Action::Set(0)],
+                vec![Action::Set(0)],
+                vec![Action::Discard]
+            ]
+        );
+
+        let exploded = vec![
+            Value::new(2).add(Action::Explode(6, ExType::Standard)),
+            Value::new(6),
+            Value::new(2),
+        ];
+
+        assert_eq!(
+            actions(&Outcomes::Match.mark(exploded)),
+            vec![
+                vec![
+                    Action::Explode(6, ExType::Standard),
+                    Action::Set(0),
+                    Action::Set(1)
+                ],
+                vec![Action::Set(0)],
+                vec![Action::Set(1)]
+            ]
+        );
+    }
+
+    #[test]
+    fn check_mark_hits() {
+        let mut values = values(vec![6, 1, 3, 5]);
+        values[3] = values[3].clone().add(Action::Discard);
+
+        assert_eq!(
+            actions(&Outcomes::Hits.mark(values.clone())),
+            vec![
+                vec![Action::Hit],
+                vec![Action::Glitch],
+                vec![],
+                vec![Action::Discard]
+            ]
+        );
+        assert_eq!(Outcomes::Total.mark(values.clone()), values);
+
+        let edge = vec![Value::new(1).add(Action::Explode(6, ExType::Standard))];
+
+        assert_eq!(
+            actions(&Outcomes::Hits.mark(edge)),
+            vec![vec![
+                Action::Explode(6, ExType::Standard),
+                Action::Hit,
+                Action::Glitch
+            ]]
+        );
+    }
+
+    #[test]
+    fn check_process_overflow() {
+        let values = values(vec![i32::MAX, 1]);
+
+        assert!(Outcomes::Total.process(&values).is_err());
+    }
+
+    #[test]
+    fn check_process_large_pool() -> Result<()> {
+        let values = values(vec![20; 200]);
+
+        assert_eq!(Outcomes::Total.process(&values)?, Outcome::Total(4000));
+        assert_eq!(
+            Outcomes::Target(Bounded::range_from(20)).process(&values)?,
+            Outcome::Target(200)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_raises() {
+        assert_eq!(
+            Outcome::raises(3, 4, false),
+            Outcome::Trait(3, Raise::Failure)
+        );
+        assert_eq!(
+            Outcome::raises(7, 4, false),
+            Outcome::Trait(7, Raise::Success(0))
+        );
+        assert_eq!(
+            Outcome::raises(8, 4, false),
+            Outcome::Trait(8, Raise::Success(1))
+        );
+        assert_eq!(
+            Outcome::raises(14, 6, false),
+            Outcome::Trait(14, Raise::Success(2))
+        );
+        assert_eq!(
+            Outcome::raises(1, 4, true),
+            Outcome::Trait(1, Raise::CriticalFailure)
+        );
+        assert_eq!(Outcome::raises(1, 4, true).value(), -1);
+        assert_eq!(Outcome::raises(3, 4, false).value(), 0);
+        assert_eq!(Outcome::raises(12, 4, false).value(), 3);
+    }
+
+    #[test]
+    fn check_parse() -> Result<()> {
+        assert_eq!("".parse::<Outcomes>()?, Outcomes::Total);
+        assert_eq!(
+            "t8".parse::<Outcomes>()?,
+            Outcomes::Target(Bounded::range_from(8))
+        );
+        assert_eq!(
+            "t>7".parse::<Outcomes>()?,
+            Outcomes::Target(Bounded::range_above(7))
+        );
+        assert_eq!(
+            "t=10".parse::<Outcomes>()?,
+            Outcomes::Target(Bounded::range_of(10))
+        );
+        assert_eq!("m".parse::<Outcomes>()?, Outcomes::Match);
+        assert_eq!("h".parse::<Outcomes>()?, Outcomes::Hits);
+        assert_eq!("<=65".parse::<Outcomes>()?, Outcomes::Skill(65));
+
+        assert!("t".parse::<Outcomes>().is_err());
+        assert!("m2".parse::<Outcomes>().is_err());
+        assert!("q".parse::<Outcomes>().is_err());
+        assert!("<65".parse::<Outcomes>().is_err());
+        assert_eq!(
+            "t8f1".parse::<Outcomes>()?,
+            Outcomes::Pool(Bounded::range_from(8), Some(Bounded::range_to(1)), None)
+        );
+        assert_eq!(
+            "t>7f<=2e4".parse::<Outcomes>()?,
+            Outcomes::Pool(Bounded::range_above(7), Some(Bounded::range_to(2)), Some(4))
+        );
+        assert_eq!(
+            "t8e4".parse::<Outcomes>()?,
+            Outcomes::Pool(Bounded::range_from(8), None, Some(4))
+        );
+        assert!("t8f".parse::<Outcomes>().is_err());
+        assert!("t8e4f1".parse::<Outcomes>().is_err());
+        assert!("t8e".parse::<Outcomes>().is_err());
+        assert!("t8q1".parse::<Outcomes>().is_err());
+
+        let target = Outcomes::Target(Bounded::range_from(8));
+
+        assert_eq!(
+            target
+                .with_exceptional(3)?
+                .with_failure(Outcomes::failure("2")?)?,
+            Outcomes::Pool(Bounded::range_from(8), Some(Bounded::range_to(2)), Some(3))
+        );
+        assert_eq!(
+            target.with_failure(Outcomes::failure("=1")?)?,
+            Outcomes::Pool(Bounded::range_from(8), Some(Bounded::range_of(1)), None)
+        );
+        assert!(Outcomes::Match.with_failure(Bounded::range_to(1)).is_err());
+        assert!(Outcomes::Total.with_exceptional(3).is_err());
+        assert!(Outcomes::failure("").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_display() {
+        assert_eq!(Outcomes::Target(Bounded::range_from(8)).to_string(), "t8");
+        assert_eq!(
+            Outcomes::Target(Bounded::range_between(5, 6)).to_string(),
+            "t5:6"
+        );
+        assert_eq!(Outcome::Total(14).to_string(), "14");
+        assert_eq!(Outcome::Target(1).to_string(), "1 success");
+        assert_eq!(
+            Outcome::Match(Sets::of([7, 7, 7, 2, 2].into_iter())).to_string(),
+            "3x7, 2x2"
+        );
+        assert_eq!(Outcomes::Skill(65).to_string(), "<=65");
+        assert_eq!(
+            Outcomes::Pool(Bounded::range_from(8), Some(Bounded::range_to(1)), Some(5)).to_string(),
+            "t8f1e5"
+        );
+        assert_eq!(Outcome::Pool(0, Grade::Botch).to_string(), "botch");
+        assert_eq!(Outcome::Hits(3, false).to_string(), "3 hits");
+        assert_eq!(Outcome::Hits(1, true).to_string(), "1 hit, glitch");
+        assert_eq!(Outcome::Hits(0, true).to_string(), "critical glitch");
+        assert_eq!(
+            Outcome::Pool(5, Grade::Exceptional).to_string(),
+            "5 successes, exceptional"
+        );
+        assert_eq!(
+            Outcome::Skill(Level::Extreme).to_string(),
+            "extreme success"
+        );
+        assert_eq!(
+            Outcome::Trait(1, Raise::CriticalFailure).to_string(),
+            "critical failure"
+        );
+        assert_eq!(
+            Outcome::Trait(5, Raise::Success(0)).to_string(),
+            "5, success"
+        );
+        assert_eq!(
+            Outcome::Trait(13, Raise::Success(2)).to_string(),
+            "13, success with 2 raises"
+        );
     }
 }
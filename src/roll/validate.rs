@@ -0,0 +1,544 @@
+use {
+    crate::{
+        dice::{bound::Bounded, Dice, Die},
+        roll::{
+            behaviour::{Behaviour, RerollType},
+            outcome::Outcomes,
+            wild::Wild,
+        },
+    },
+    std::{
+        error,
+        fmt::{self, Display},
+        ops::RangeBounds,
+    },
+};
+
+/// A roll that cannot be made, found before any dice are rolled.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ValidationError {
+    EmptyDice(Dice),
+    NoTensDie(Die, Behaviour),
+    ImpossibleReroll(Die, Bounded),
+    EndlessExplode(Die, Bounded),
+    Unreachable(Die, Behaviour),
+    KeepTooMany(Die, usize, usize),
+    DropTooMany(Die, usize, usize),
+    NotTraitDie(Dice),
+    NarrativeOutcome(Die, Outcomes),
+    NotHitDie(Die),
+    NotSkillDie(Die),
+    WildArithmetic(Wild),
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::EmptyDice(dice) => write!(f, "{} has no faces to roll", dice),
+            Self::NoTensDie(die, behaviour) => write!(
+                f,
+                "{} has no tens die for `{}`, only percentile dice do",
+                die, behaviour
+            ),
+            Self::ImpossibleReroll(die, range) => write!(
+                f,
+                "{} rerolls every face in {}, so it never finishes",
+                die, range
+            ),
+            Self::EndlessExplode(die, range) => write!(
+                f,
+                "{} explodes on every face in {}, so it never finishes",
+                die, range
+            ),
+            Self::Unreachable(die, behaviour) => write!(
+                f,
+                "{} never rolls a face matching `{}`, so it has no effect",
+                die, behaviour
+            ),
+            Self::KeepTooMany(die, number, available) => write!(
+                f,
+                "{} cannot keep {} dice when only {} are left",
+                die, number, available
+            ),
+            Self::DropTooMany(die, number, available) => write!(
+                f,
+                "{} cannot drop {} dice when only {} are left",
+                die, number, available
+            ),
+            Self::NotTraitDie(dice) => {
+                write!(f, "{} has no number to roll as a trait die", dice)
+            }
+            Self::NarrativeOutcome(die, outcomes) => write!(
+                f,
+                "{} always totals its symbols, so it cannot take the outcome `{}`",
+                die, outcomes
+            ),
+            Self::NotHitDie(die) => {
+                write!(
+                    f,
+                    "{} is not a d6, so it has no hits on 5 and 6 to count",
+                    die
+                )
+            }
+            Self::NotSkillDie(die) => write!(
+                f,
+                "{} is not a single percentile die, so it cannot roll under a skill",
+                die
+            ),
+            Self::WildArithmetic(wild) => write!(
+                f,
+                "{} reads as raises rather than a number, so it cannot be used in arithmetic; \
+                 add a modifier inside it instead, as `sw(d8+2)`",
+                wild
+            ),
+        }
+    }
+}
+
+impl error::Error for ValidationError {}
+
+/// Check that `behaviours` can be applied to a roll of `die` without failing or looping forever,
+/// and that the dice can give `outcomes`.
+pub fn validate(
+    die: &Die,
+    behaviours: &[Behaviour],
+    outcomes: &Outcomes,
+) -> Result<(), ValidationError> {
+    let dice = die.dice();
+    let faces = dice.faces();
+    if faces.is_empty() {
+        return Err(ValidationError::EmptyDice(dice.clone()));
+    }
+    if matches!(dice, Dice::Symbolic(_)) && *outcomes != Outcomes::Total {
+        return Err(ValidationError::NarrativeOutcome(die.clone(), *outcomes));
+    }
+    if *dice != Dice::D6 && *outcomes == Outcomes::Hits {
+        return Err(ValidationError::NotHitDie(die.clone()));
+    }
+    if matches!(outcomes, Outcomes::Skill(_)) && !(dice.has_tens() && die.count() == 1) {
+        return Err(ValidationError::NotSkillDie(die.clone()));
+    }
+    let sides = dice.sides();
+
+    let mut behaviours = behaviours.to_vec();
+    behaviours.sort_unstable();
+    let mut available = die.count() as usize;
+    for behaviour in behaviours {
+        if points(&behaviour)
+            .iter()
+            .any(|range| !sides.iter().any(|side| range.contains(side)))
+        {
+            return Err(ValidationError::Unreachable(die.clone(), behaviour));
+        }
+        match behaviour {
+            Behaviour::Reroll(point, RerollType::Repeat, None) => {
+                if let Some(range) = Behaviour::failure(dice, point) {
+                    if range.covers(&faces) {
+                        return Err(ValidationError::ImpossibleReroll(die.clone(), range));
+                    }
+                }
+            }
+            Behaviour::RerollTens(..) | Behaviour::Bonus(_) | Behaviour::Penalty(_)
+                if !dice.has_tens() =>
+            {
+                return Err(ValidationError::NoTensDie(die.clone(), behaviour));
+            }
+            Behaviour::RerollTens(point, RerollType::Repeat) => {
+                // the units die is kept, so every tens die for one of them must escape the range
+                if let Some(range) = Behaviour::failure(dice, point) {
+                    if (0..10).any(|units| {
+                        (0..10).all(|tens| range.contains(&Dice::percentile(tens, units)))
+                    }) {
+                        return Err(ValidationError::ImpossibleReroll(die.clone(), range));
+                    }
+                }
+            }
+            Behaviour::Explode(point, _, None) => {
+                if let Some(range) = Behaviour::success(dice, point) {
+                    if range.covers(&faces) {
+                        return Err(ValidationError::EndlessExplode(die.clone(), range));
+                    }
+                }
+            }
+            Behaviour::Keep(number, _) if number > available => {
+                return Err(ValidationError::KeepTooMany(die.clone(), number, available));
+            }
+            Behaviour::Keep(number, _) => available = number,
+            Behaviour::Drop(number, _) if number > available => {
+                return Err(ValidationError::DropTooMany(die.clone(), number, available));
+            }
+            Behaviour::Drop(number, _) => available -= number,
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// The ranges given explicitly to a behaviour that tests single faces, rather than taken from
+/// the dice. Critical points test the value after any compounding or penetration, which can
+/// pass the faces, so they are left alone.
+fn points(behaviour: &Behaviour) -> Vec<Bounded> {
+    match behaviour {
+        Behaviour::Reroll(point, _, _)
+        | Behaviour::RerollTens(point, _)
+        | Behaviour::Explode(point, _, _) => point.iter().copied().collect(),
+        Behaviour::Critical(..)
+        | Behaviour::Bonus(_)
+        | Behaviour::Penalty(_)
+        | Behaviour::Keep(..)
+        | Behaviour::Drop(..) => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::{
+            dice::narrative::Narrative,
+            roll::{behaviour::DiscardDirection::*, value::ExType},
+        },
+    };
+
+    #[test]
+    fn check_valid() {
+        let die = Die::new(Dice::D6, 4);
+
+        assert_eq!(
+            validate(
+                &die,
+                &[
+                    Behaviour::Reroll(None, RerollType::Repeat, None),
+                    Behaviour::Explode(None, ExType::Standard, None),
+                    Behaviour::Keep(3, High),
+                    Behaviour::Drop(3, Low),
+                ],
+                &Outcomes::Total
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn check_empty_dice() {
+        let die = Die::new(Dice::Other(5, 2), 1);
+
+        assert_eq!(
+            validate(&die, &[], &Outcomes::Total),
+            Err(ValidationError::EmptyDice(Dice::Other(5, 2)))
+        );
+    }
+
+    #[test]
+    fn check_impossible_reroll() {
+        let die = Die::new(Dice::D6, 1);
+        let all = Bounded::from_range(..=6);
+
+        assert_eq!(
+            validate(
+                &die,
+                &[Behaviour::Reroll(Some(all), RerollType::Repeat, None)],
+                &Outcomes::Total
+            ),
+            Err(ValidationError::ImpossibleReroll(die.clone(), all))
+        );
+        assert_eq!(
+            validate(
+                &die,
+                &[Behaviour::Reroll(Some(all), RerollType::Once, None)],
+                &Outcomes::Total
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            validate(
+                &die,
+                &[Behaviour::Reroll(Some(all), RerollType::High, None)],
+                &Outcomes::Total
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn check_endless_explode() {
+        let die = Die::new(Dice::Other(1, 1), 1);
+
+        assert_eq!(
+            validate(
+                &die,
+                &[Behaviour::Explode(None, ExType::Compound, None)],
+                &Outcomes::Total
+            ),
+            Err(ValidationError::EndlessExplode(
+                die.clone(),
+                Bounded::from_range(1..)
+            ))
+        );
+        assert_eq!(
+            validate(
+                &die,
+                &[Behaviour::Explode(None, ExType::Compound, Some(3))],
+                &Outcomes::Total
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            validate(
+                &die,
+                &[Behaviour::Reroll(None, RerollType::Repeat, Some(3))],
+                &Outcomes::Total
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn check_unreachable() {
+        let die = Die::new(Dice::D6, 1);
+        let explode = Behaviour::Explode(Some(Bounded::range_above(6)), ExType::Standard, None);
+        let critical = Behaviour::Critical(None, Some(Bounded::range_from(19)));
+
+        assert_eq!(
+            validate(&die, &[explode], &Outcomes::Total),
+            Err(ValidationError::Unreachable(die.clone(), explode))
+        );
+        assert_eq!(validate(&die, &[critical], &Outcomes::Total), Ok(()));
+        assert_eq!(
+            validate(
+                &Die::new(Dice::D6, 3),
+                &[
+                    Behaviour::Explode(None, ExType::Compound, None),
+                    Behaviour::Critical(None, Some(Bounded::range_from(10))),
+                ],
+                &Outcomes::Total
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            validate(
+                &die,
+                &[Behaviour::Reroll(
+                    Some(Bounded::range_below(2)),
+                    RerollType::Once,
+                    None,
+                )],
+                &Outcomes::Total
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            ValidationError::Unreachable(die, explode).to_string(),
+            "1d6 never rolls a face matching `!>6`, so it has no effect"
+        );
+    }
+
+    #[test]
+    fn check_unreachable_custom() {
+        let die = Die::new(Dice::Custom(vec![1, 1, 8]), 1);
+        let gap = Behaviour::Reroll(Some(Bounded::range_between(2, 7)), RerollType::Once, None);
+
+        assert_eq!(
+            validate(&die, &[gap], &Outcomes::Total),
+            Err(ValidationError::Unreachable(die.clone(), gap))
+        );
+        assert_eq!(
+            validate(
+                &die,
+                &[Behaviour::Reroll(
+                    Some(Bounded::range_of(1)),
+                    RerollType::Repeat,
+                    None
+                )],
+                &Outcomes::Total
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn check_impossible_reroll_excluded() {
+        let die = Die::new(Dice::D6, 1);
+
+        assert_eq!(
+            validate(
+                &die,
+                &[Behaviour::Reroll(
+                    Some(Bounded::range_below(7)),
+                    RerollType::Repeat,
+                    None,
+                )],
+                &Outcomes::Total
+            ),
+            Err(ValidationError::ImpossibleReroll(
+                die.clone(),
+                Bounded::range_below(7)
+            ))
+        );
+        assert_eq!(
+            validate(
+                &die,
+                &[Behaviour::Reroll(
+                    Some(Bounded::range_below(6)),
+                    RerollType::Repeat,
+                    None,
+                )],
+                &Outcomes::Total
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn check_narrative_outcome() {
+        let die = Die::new(Dice::from(Narrative::Ability), 2);
+        let target = Outcomes::Target(Bounded::range_from(3));
+
+        assert_eq!(validate(&die, &[], &Outcomes::Total), Ok(()));
+        assert_eq!(
+            validate(&die, &[], &target),
+            Err(ValidationError::NarrativeOutcome(die.clone(), target))
+        );
+        assert_eq!(
+            ValidationError::NarrativeOutcome(die, Outcomes::Match).to_string(),
+            "2dAbility always totals its symbols, so it cannot take the outcome `m`"
+        );
+    }
+
+    #[test]
+    fn check_hits() {
+        let die = Die::new(Dice::D10, 4);
+
+        assert_eq!(
+            validate(&Die::new(Dice::D6, 4), &[], &Outcomes::Hits),
+            Ok(())
+        );
+        assert_eq!(
+            validate(&die, &[], &Outcomes::Hits),
+            Err(ValidationError::NotHitDie(die.clone()))
+        );
+        assert_eq!(
+            ValidationError::NotHitDie(die).to_string(),
+            "4d10 is not a d6, so it has no hits on 5 and 6 to count"
+        );
+    }
+
+    #[test]
+    fn check_skill() {
+        let skill = Outcomes::Skill(50);
+
+        assert_eq!(validate(&Die::new(Dice::D100, 1), &[], &skill), Ok(()));
+        assert_eq!(
+            validate(&Die::new(Dice::Percentile, 1), &[], &skill),
+            Ok(())
+        );
+        assert_eq!(
+            validate(&Die::new(Dice::D6, 1), &[], &Outcomes::Skill(4)),
+            Err(ValidationError::NotSkillDie(Die::new(Dice::D6, 1)))
+        );
+        assert_eq!(
+            validate(&Die::new(Dice::D100, 2), &[], &skill),
+            Err(ValidationError::NotSkillDie(Die::new(Dice::D100, 2)))
+        );
+        assert_eq!(
+            ValidationError::NotSkillDie(Die::new(Dice::D6, 1)).to_string(),
+            "1d6 is not a single percentile die, so it cannot roll under a skill"
+        );
+    }
+
+    #[test]
+    fn check_reroll_tens() {
+        let tens = Behaviour::RerollTens(Some(Bounded::range_from(15)), RerollType::Repeat);
+
+        assert_eq!(
+            validate(&Die::new(Dice::Percentile, 1), &[tens], &Outcomes::Total),
+            Ok(())
+        );
+        assert_eq!(
+            validate(
+                &Die::new(Dice::D100, 1),
+                &[tens, Behaviour::Bonus(2)],
+                &Outcomes::Total
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            validate(&Die::new(Dice::D20, 1), &[tens], &Outcomes::Total),
+            Err(ValidationError::NoTensDie(Die::new(Dice::D20, 1), tens))
+        );
+        assert_eq!(
+            validate(
+                &Die::new(Dice::D20, 1),
+                &[Behaviour::Penalty(1)],
+                &Outcomes::Total
+            ),
+            Err(ValidationError::NoTensDie(
+                Die::new(Dice::D20, 1),
+                Behaviour::Penalty(1)
+            ))
+        );
+
+        let die = Die::new(Dice::Percentile, 1);
+        let zero = Bounded::range_from(10);
+
+        assert_eq!(
+            validate(
+                &die,
+                &[Behaviour::RerollTens(Some(zero), RerollType::Repeat)],
+                &Outcomes::Total
+            ),
+            Err(ValidationError::ImpossibleReroll(die.clone(), zero))
+        );
+        assert_eq!(
+            validate(
+                &die,
+                &[Behaviour::RerollTens(Some(zero), RerollType::Once)],
+                &Outcomes::Total
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn check_discard_too_many() {
+        let die = Die::new(Dice::D6, 2);
+
+        assert_eq!(
+            validate(&die, &[Behaviour::Keep(5, High)], &Outcomes::Total),
+            Err(ValidationError::KeepTooMany(die.clone(), 5, 2))
+        );
+        assert_eq!(
+            validate(&die, &[Behaviour::Drop(3, Low)], &Outcomes::Total),
+            Err(ValidationError::DropTooMany(die.clone(), 3, 2))
+        );
+        assert_eq!(
+            validate(
+                &die,
+                &[Behaviour::Keep(2, High), Behaviour::Drop(3, Low)],
+                &Outcomes::Total
+            ),
+            Err(ValidationError::DropTooMany(die, 3, 2))
+        );
+        assert_eq!(
+            validate(
+                &Die::new(Dice::D6, 4),
+                &[Behaviour::Keep(2, High), Behaviour::Drop(2, Low)],
+                &Outcomes::Total
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn check_display() {
+        assert_eq!(
+            ValidationError::KeepTooMany(Die::new(Dice::D6, 2), 5, 2).to_string(),
+            "2d6 cannot keep 5 dice when only 2 are left"
+        );
+        assert_eq!(
+            ValidationError::ImpossibleReroll(Die::new(Dice::D6, 1), Bounded::from_range(..=6))
+                .to_string(),
+            "1d6 rerolls every face in ..=6, so it never finishes"
+        );
+    }
+}
@@ -1,54 +1,122 @@
 use {
     crate::dice::Dice,
-    colored::Colorize,
+    colored::{Color, Colorize},
     joinery::{separators::Space, JoinableIterator},
+    serde::{ser::SerializeStruct, Serialize, Serializer},
     std::fmt::{self, Display},
 };
 
-#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone, Copy)]
+/// Colours given to the dice of each matched set in turn.
+const SETS: [Color; 6] = [
+    Color::Cyan,
+    Color::Magenta,
+    Color::Yellow,
+    Color::Blue,
+    Color::BrightGreen,
+    Color::BrightRed,
+];
+
+#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone, Copy, Serialize)]
 pub enum ExType {
     Standard,
     Compound,
     Penetrating,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub enum TensType {
+    Bonus,
+    Penalty,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
 pub enum Action {
     Discard,
-    Reroll(i8),
-    Explode(i8, ExType),
+    Reroll(i32),
+    Unused(i32),
+    Explode(i32, ExType),
+    Tens(i32, TensType),
     Failure,
     Success,
+    Capped,
+    Bonus(i32),
+    Penalty(i32),
+    Hit,
+    Glitch,
+    Set(u32),
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Discard => write!(f, "discarded"),
+            Self::Reroll(value) => write!(f, "rerolled {}", value),
+            Self::Unused(value) => write!(f, "kept over reroll {}", value),
+            Self::Explode(value, ExType::Standard) => write!(f, "exploded {}", value),
+            Self::Explode(value, ExType::Compound) => write!(f, "compounded {}", value),
+            Self::Explode(value, ExType::Penetrating) => write!(f, "penetrated {}", value),
+            Self::Tens(value, TensType::Bonus) => write!(f, "bonus die {}", value),
+            Self::Tens(value, TensType::Penalty) => write!(f, "penalty die {}", value),
+            Self::Failure => write!(f, "critical failure"),
+            Self::Success => write!(f, "critical success"),
+            Self::Capped => write!(f, "stopped at limit"),
+            Self::Bonus(tens) => write!(f, "bonus die kept {}0", tens),
+            Self::Penalty(tens) => write!(f, "penalty die kept {}0", tens),
+            Self::Hit => write!(f, "hit"),
+            Self::Glitch => write!(f, "counts toward a glitch"),
+            Self::Set(index) => write!(f, "in set {}", index + 1),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Value {
-    value: i8,
+    value: i32,
     actions: Vec<Action>,
 }
 
 impl Value {
-    pub fn new(value: i8) -> Self {
+    pub fn new(value: i32) -> Self {
         Self {
             value,
             actions: Vec::new(),
         }
     }
 
-    pub fn value(&self) -> i8 {
+    pub fn value(&self) -> i32 {
         self.value
     }
 
+    /// Tens and units dice of a percentile value, where 100 shows `00` and `0`.
+    pub fn percentile(&self) -> (i32, i32) {
+        (self.value % 100 / 10, self.value % 10)
+    }
+
     pub fn actions(&self) -> &Vec<Action> {
         &self.actions
     }
 
-    pub fn add(mut self, action: Action) -> Self {
+    /// Every face rolled for this value, including those that triggered a standard or
+    /// penetrating explosion; a compounded value already holds the sum of its rolls.
+    pub fn faces(&self) -> Vec<i32> {
+        let mut faces: Vec<i32> = self
+            .actions
+            .iter()
+            .filter_map(|a| match a {
+                Action::Explode(value, ExType::Standard | ExType::Penetrating) => Some(*value),
+                _ => None,
+            })
+            .collect();
+        faces.push(self.value);
+        faces
+    }
+
+    pub(crate) fn add(mut self, action: Action) -> Self {
         self.actions.push(action);
         self
     }
 
-    pub fn update(mut self, value: i8, action: Action) -> Self {
+    pub(crate) fn update(mut self, value: i32, action: Action) -> Self {
         self.value = value;
         self.actions.push(action);
         self
@@ -60,6 +128,9 @@ impl Value {
 
     fn format_text(&self, text: String) -> String {
         let mut modifiers = (false, false, false, false, false);
+        let mut capped = false;
+        let mut tens = false;
+        let mut set = None;
         let mut reroll = Vec::new();
         let mut explode = Vec::new();
         let mut text = text.normal();
@@ -67,13 +138,16 @@ impl Value {
         for action in self.actions() {
             match action {
                 Action::Discard => modifiers.0 = true,
-                Action::Failure => modifiers.1 = true,
-                Action::Success => modifiers.2 = true,
+                Action::Failure | Action::Glitch => modifiers.1 = true,
+                Action::Success | Action::Hit => modifiers.2 = true,
+                Action::Capped => capped = true,
+                Action::Bonus(_) | Action::Penalty(_) => tens = true,
+                Action::Set(index) => set = Some(SETS[*index as usize % SETS.len()]),
                 Action::Explode(value, _) => {
                     modifiers.3 = true;
                     explode.push(value);
                 }
-                Action::Reroll(value) => {
+                Action::Reroll(value) | Action::Unused(value) | Action::Tens(value, _) => {
                     modifiers.4 = true;
                     reroll.push(value);
                 }
@@ -92,7 +166,7 @@ impl Value {
             )
             .dimmed()
         };
-        let post = if explode.is_empty() {
+        let mut post = if explode.is_empty() {
             "".to_string()
         } else {
             format!(
@@ -121,10 +195,32 @@ impl Value {
         } else if modifiers.4 {
             // rerolled
         }
+        if let Some(colour) = set {
+            // dice in the same matched set
+            text = text.color(colour).bold();
+        }
+        if tens {
+            // tens die chosen by bonus or penalty dice
+            text = text.underline();
+        }
+        if capped {
+            post.push('…');
+        }
         format!("{}{}{}", pre, text, post)
     }
 }
 
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut value = serializer.serialize_struct("Value", 4)?;
+        value.serialize_field("value", &self.value)?;
+        value.serialize_field("faces", &self.faces())?;
+        value.serialize_field("discarded", &self.actions.contains(&Action::Discard))?;
+        value.serialize_field("actions", &self.actions)?;
+        value.end()
+    }
+}
+
 impl Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.format_text(self.value().to_string()))
@@ -135,19 +231,74 @@ impl Display for Value {
 pub(crate) mod test {
     use super::*;
 
-    pub(crate) fn values(values: Vec<i8>) -> Vec<Value> {
+    pub(crate) fn values(values: Vec<i32>) -> Vec<Value> {
         values.iter().map(|v| Value::new(*v)).collect()
     }
 
-    pub(crate) fn action<'a>(values: &'a Vec<Value>) -> Vec<Option<Action>> {
+    pub(crate) fn action(values: &[Value]) -> Vec<Option<Action>> {
         values
             .iter()
             .filter(|v| v.actions().len() < 2)
-            .map(|v| v.actions().get(0).map(|v| *v).or(None))
+            .map(|v| v.actions().first().copied())
             .collect()
     }
 
-    pub(crate) fn actions<'a>(values: &'a Vec<Value>) -> Vec<Vec<Action>> {
+    pub(crate) fn actions(values: &[Value]) -> Vec<Vec<Action>> {
         values.iter().map(|v| v.actions().clone()).collect()
     }
+
+    #[test]
+    fn check_action_display() {
+        assert_eq!(Action::Reroll(1).to_string(), "rerolled 1");
+        assert_eq!(Action::Unused(2).to_string(), "kept over reroll 2");
+        assert_eq!(
+            Action::Tens(97, TensType::Bonus).to_string(),
+            "bonus die 97"
+        );
+        assert_eq!(
+            Action::Tens(25, TensType::Penalty).to_string(),
+            "penalty die 25"
+        );
+        assert_eq!(Action::Capped.to_string(), "stopped at limit");
+        assert_eq!(Action::Bonus(0).to_string(), "bonus die kept 00");
+        assert_eq!(Action::Penalty(7).to_string(), "penalty die kept 70");
+        assert_eq!(Action::Glitch.to_string(), "counts toward a glitch");
+        assert_eq!(Action::Set(0).to_string(), "in set 1");
+        assert_eq!(
+            Action::Explode(6, ExType::Penetrating).to_string(),
+            "penetrated 6"
+        );
+        assert_eq!(
+            Value::new(4)
+                .add(Action::Explode(6, ExType::Standard))
+                .add(Action::Discard)
+                .actions()
+                .iter()
+                .join_with(", ")
+                .to_string(),
+            "exploded 6, discarded"
+        );
+    }
+
+    #[test]
+    fn check_percentile() {
+        assert_eq!(Value::new(37).percentile(), (3, 7));
+        assert_eq!(Value::new(5).percentile(), (0, 5));
+        assert_eq!(Value::new(100).percentile(), (0, 0));
+    }
+
+    #[test]
+    fn check_serialize() -> serde_json::Result<()> {
+        let value = Value::new(3)
+            .add(Action::Reroll(1))
+            .add(Action::Explode(6, ExType::Standard))
+            .add(Action::Discard);
+
+        assert_eq!(
+            serde_json::to_string(&value)?,
+            r#"{"value":3,"faces":[6,3],"discarded":true,"actions":[{"Reroll":1},{"Explode":[6,"Standard"]},"Discard"]}"#
+        );
+
+        Ok(())
+    }
 }
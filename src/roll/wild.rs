@@ -0,0 +1,242 @@
+use {
+    crate::{
+        dice::{Dice, Die},
+        roll::{
+            behaviour::Behaviour,
+            outcome::{Outcome, Outcomes},
+            validate::{validate, ValidationError},
+            value::{Action, ExType},
+            Roll,
+        },
+    },
+    anyhow::Result,
+    rand::RngCore,
+    std::fmt::{self, Display},
+};
+
+/// Target number of a trait roll that does not set its own.
+pub const TARGET: i32 = 4;
+
+/// A Savage Worlds trait roll, such as `sw(d8)`: the trait die and a d6 wild die, both acing,
+/// keeping the higher against a target number. A modifier, as `sw(d8+2)`, adds to the kept
+/// total; the roll stands alone and is not an operand of arithmetic, as its value is the raises
+/// rather than the total.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Wild {
+    dice: Dice,
+    modifier: i32,
+    target: i32,
+}
+
+impl Wild {
+    pub fn new(dice: Dice) -> Self {
+        Self {
+            dice,
+            modifier: 0,
+            target: TARGET,
+        }
+    }
+
+    pub fn with_modifier(mut self, modifier: i32) -> Self {
+        self.modifier = modifier;
+        self
+    }
+
+    pub fn with_target(mut self, target: i32) -> Self {
+        self.target = target;
+        self
+    }
+
+    pub fn dice(&self) -> &Dice {
+        &self.dice
+    }
+
+    pub fn modifier(&self) -> i32 {
+        self.modifier
+    }
+
+    pub fn target(&self) -> i32 {
+        self.target
+    }
+
+    /// The trait die followed by the wild die.
+    pub fn dies(&self) -> [Die; 2] {
+        [Die::new(self.dice.clone(), 1), Die::new(Dice::D6, 1)]
+    }
+
+    /// Both dice ace, adding a further roll each time they show their highest face.
+    pub fn behaviours() -> Vec<Behaviour> {
+        vec![Behaviour::Explode(None, ExType::Compound, None)]
+    }
+
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if let Dice::Symbolic(_) = self.dice {
+            return Err(ValidationError::NotTraitDie(self.dice.clone()));
+        }
+        for die in self.dies() {
+            validate(&die, &Self::behaviours(), &Outcomes::Total)?;
+        }
+        Ok(())
+    }
+
+    /// Roll the trait and wild dice as two groups sharing a keep highest, marking both on
+    /// snake eyes.
+    pub fn roll(&self, limit: usize, rng: &mut dyn RngCore) -> Result<Vec<Roll>> {
+        let mut rolls = Vec::new();
+        for die in self.dies() {
+            let mut roll = Roll::from_roll(die, rng)?;
+            roll.apply(Self::behaviours(), limit, rng)?;
+            rolls.push(roll);
+        }
+        Roll::keep_across(&mut rolls, 1);
+        if Self::snake_eyes(&Self::totals(&rolls)) {
+            for roll in rolls.iter_mut() {
+                roll.add(Action::Failure);
+            }
+        }
+        Ok(rolls)
+    }
+
+    pub fn outcome(&self, rolls: &[Roll]) -> Outcome {
+        self.resolve(&Self::totals(rolls))
+    }
+
+    /// Outcome of the aced totals of the trait and wild dice, the modifier adding to the higher
+    /// while snake eyes still reads the dice as rolled.
+    pub fn resolve(&self, totals: &[i32]) -> Outcome {
+        Outcome::raises(
+            totals
+                .iter()
+                .copied()
+                .max()
+                .unwrap_or_default()
+                .saturating_add(self.modifier),
+            self.target,
+            Self::snake_eyes(totals),
+        )
+    }
+
+    fn totals(rolls: &[Roll]) -> Vec<i32> {
+        rolls
+            .iter()
+            .flat_map(|r| r.values().iter().map(|v| v.value()))
+            .collect()
+    }
+
+    /// Both dice show 1, which can only be a natural roll as an aced die totals more.
+    fn snake_eyes(totals: &[i32]) -> bool {
+        totals.iter().all(|v| *v == 1)
+    }
+}
+
+impl Display for Wild {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.modifier {
+            0 => write!(f, "sw({})", self.dice)?,
+            modifier => write!(f, "sw({}{:+})", self.dice, modifier)?,
+        }
+        if self.target != TARGET {
+            write!(f, "t{}", self.target)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::{
+            dice::narrative::Narrative,
+            mock::rng::*,
+            roll::{outcome::Raise, value::test::*},
+        },
+    };
+
+    #[test]
+    fn check_roll() -> Result<()> {
+        // the trait die rolls 3, the wild die 6 then 2 for 8
+        let mut rng = seq_rng(Dice::D6, [2, 5, 1].into_iter());
+        let wild = Wild::new(Dice::D6);
+
+        let rolls = wild.roll(10, &mut rng)?;
+
+        assert_eq!(actions(rolls[0].values()), vec![vec![Action::Discard]]);
+        assert_eq!(rolls[1].values()[0].value(), 8);
+        assert_eq!(wild.outcome(&rolls), Outcome::Trait(8, Raise::Success(1)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_roll_snake_eyes() -> Result<()> {
+        let mut rng = seq_rng(Dice::D6, [0, 0].into_iter());
+        let wild = Wild::new(Dice::D6);
+
+        let rolls = wild.roll(10, &mut rng)?;
+
+        assert_eq!(
+            actions(rolls[0].values()),
+            vec![vec![Action::Discard, Action::Failure]]
+        );
+        assert_eq!(actions(rolls[1].values()), vec![vec![Action::Failure]]);
+        assert_eq!(
+            wild.outcome(&rolls),
+            Outcome::Trait(1, Raise::CriticalFailure)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_resolve() {
+        let wild = Wild::new(Dice::D8).with_target(6);
+
+        assert_eq!(wild.resolve(&[5, 3]), Outcome::Trait(5, Raise::Failure));
+        assert_eq!(
+            wild.resolve(&[11, 2]),
+            Outcome::Trait(11, Raise::Success(1))
+        );
+        assert_eq!(
+            wild.resolve(&[1, 1]),
+            Outcome::Trait(1, Raise::CriticalFailure)
+        );
+        assert_eq!(wild.resolve(&[1, 9]), Outcome::Trait(9, Raise::Success(0)));
+    }
+
+    #[test]
+    fn check_resolve_modifier() {
+        let wild = Wild::new(Dice::D8).with_modifier(2);
+
+        assert_eq!(wild.resolve(&[3, 1]), Outcome::Trait(5, Raise::Success(0)));
+        assert_eq!(wild.resolve(&[6, 2]), Outcome::Trait(8, Raise::Success(1)));
+        assert_eq!(
+            wild.resolve(&[1, 1]),
+            Outcome::Trait(3, Raise::CriticalFailure)
+        );
+        assert_eq!(
+            Wild::new(Dice::D8).with_modifier(-2).resolve(&[5, 2]),
+            Outcome::Trait(3, Raise::Failure)
+        );
+    }
+
+    #[test]
+    fn check_validate() {
+        assert_eq!(Wild::new(Dice::D4).validate(), Ok(()));
+        assert_eq!(
+            Wild::new(Dice::from(Narrative::Ability)).validate(),
+            Err(ValidationError::NotTraitDie(Dice::from(Narrative::Ability)))
+        );
+    }
+
+    #[test]
+    fn check_display() {
+        assert_eq!(Wild::new(Dice::D8).to_string(), "sw(d8)");
+        assert_eq!(Wild::new(Dice::D12).with_target(6).to_string(), "sw(d12)t6");
+        assert_eq!(Wild::new(Dice::D8).with_modifier(2).to_string(), "sw(d8+2)");
+        assert_eq!(
+            Wild::new(Dice::D8).with_modifier(-1).to_string(),
+            "sw(d8-1)"
+        );
+    }
+}
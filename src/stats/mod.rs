@@ -0,0 +1,302 @@
+pub mod roll;
+pub mod simulate;
+
+use {
+    crate::roll::{
+        behaviour::DEFAULT_LIMIT,
+        expression::{Expression, Operator},
+        outcome::Outcomes,
+        validate::validate,
+        wild::Wild,
+    },
+    anyhow::{anyhow, Result},
+    std::{
+        collections::BTreeMap,
+        fmt::{self, Display},
+    },
+};
+
+/// Exact discrete probability distribution over integer results.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Distribution {
+    probabilities: BTreeMap<i32, f64>,
+}
+
+impl Distribution {
+    pub fn new(probabilities: BTreeMap<i32, f64>) -> Self {
+        Self {
+            probabilities: probabilities
+                .into_iter()
+                .filter(|(_, p)| *p > 0.0)
+                .collect(),
+        }
+    }
+
+    pub fn constant(value: i32) -> Self {
+        Self::new(BTreeMap::from([(value, 1.0)]))
+    }
+
+    pub fn uniform<I: IntoIterator<Item = i32>>(values: I) -> Self {
+        let values: Vec<i32> = values.into_iter().collect();
+        let p = 1.0 / values.len() as f64;
+        let mut probabilities = BTreeMap::new();
+        for value in values {
+            *probabilities.entry(value).or_insert(0.0) += p;
+        }
+        Self::new(probabilities)
+    }
+
+    pub fn of(expression: &Expression) -> Result<Self> {
+        Self::of_with_limit(expression, DEFAULT_LIMIT)
+    }
+
+    /// As [`of`](Self::of), stopping any reroll or explosion without a limit of its own after
+    /// `limit` iterations of a die.
+    pub fn of_with_limit(expression: &Expression, limit: usize) -> Result<Self> {
+        match expression {
+            Expression::Constant(value) => Ok(Self::constant(*value)),
+            Expression::Roll(die, behaviours, outcomes) => {
+                validate(die, behaviours, outcomes)?;
+                roll::distribution_with_limit(die, behaviours, outcomes, limit)
+            }
+            Expression::Wild(wild) => {
+                wild.validate()?;
+                let [trait_die, wild_die] = wild.dies();
+                let behaviours = Wild::behaviours();
+                let thrown =
+                    |die| roll::distribution_with_limit(die, &behaviours, &Outcomes::Total, limit);
+                thrown(&trait_die)?.combine(&thrown(&wild_die)?, |t, w| {
+                    Ok(wild.resolve(&[t, w]).value())
+                })
+            }
+            Expression::Negate(expression) => Self::of_with_limit(expression.operand()?, limit)?
+                .map(|v| {
+                    v.checked_neg()
+                        .ok_or_else(|| anyhow!("Overflow negating {}", v))
+                }),
+            Expression::Binary(operator, lhs, rhs) => {
+                let rhs = Self::of_with_limit(rhs.operand()?, limit)?;
+                if *operator == Operator::Divide && rhs.probability(0) > 0.0 {
+                    return Err(anyhow!("Division by zero is possible"));
+                }
+                Self::of_with_limit(lhs.operand()?, limit)?
+                    .combine(&rhs, |l, r| operator.apply(l, r))
+            }
+        }
+    }
+
+    pub fn probabilities(&self) -> &BTreeMap<i32, f64> {
+        &self.probabilities
+    }
+
+    pub fn probability(&self, value: i32) -> f64 {
+        self.probabilities.get(&value).copied().unwrap_or_default()
+    }
+
+    /// P(X >= value)
+    pub fn at_least(&self, value: i32) -> f64 {
+        self.probabilities.range(value..).map(|(_, p)| p).sum()
+    }
+
+    /// P(X <= value)
+    pub fn at_most(&self, value: i32) -> f64 {
+        self.probabilities.range(..=value).map(|(_, p)| p).sum()
+    }
+
+    pub fn min(&self) -> Option<i32> {
+        self.probabilities.keys().next().copied()
+    }
+
+    pub fn max(&self) -> Option<i32> {
+        self.probabilities.keys().next_back().copied()
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.probabilities.iter().map(|(v, p)| *v as f64 * p).sum()
+    }
+
+    pub fn variance(&self) -> f64 {
+        let mean = self.mean();
+        self.probabilities
+            .iter()
+            .map(|(v, p)| (*v as f64 - mean).powi(2) * p)
+            .sum()
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// The smallest value whose cumulative probability reaches `percent`.
+    pub fn percentile(&self, percent: f64) -> Option<i32> {
+        let target = percent / 100.0 - f64::EPSILON * 16.0;
+        let mut cumulative = 0.0;
+        for (value, p) in &self.probabilities {
+            cumulative += p;
+            if cumulative >= target {
+                return Some(*value);
+            }
+        }
+        self.max()
+    }
+
+    pub fn map<F>(&self, f: F) -> Result<Distribution>
+    where
+        F: Fn(i32) -> Result<i32>,
+    {
+        let mut probabilities = BTreeMap::new();
+        for (value, p) in &self.probabilities {
+            *probabilities.entry(f(*value)?).or_insert(0.0) += p;
+        }
+        Ok(Self::new(probabilities))
+    }
+
+    /// Distribution of `f(x, y)` where `x` and `y` are drawn independently from each distribution.
+    pub fn combine<F>(&self, other: &Distribution, f: F) -> Result<Distribution>
+    where
+        F: Fn(i32, i32) -> Result<i32>,
+    {
+        let mut probabilities = BTreeMap::new();
+        for (x, px) in &self.probabilities {
+            for (y, py) in &other.probabilities {
+                *probabilities.entry(f(*x, *y)?).or_insert(0.0) += px * py;
+            }
+        }
+        Ok(Self::new(probabilities))
+    }
+
+    pub(crate) fn add(&self, other: &Distribution) -> Result<Distribution> {
+        self.combine(other, |x, y| {
+            x.checked_add(y)
+                .ok_or_else(|| anyhow!("Overflow adding {} and {}", x, y))
+        })
+    }
+
+    /// Distribution of the sum of `count` independent draws.
+    pub(crate) fn repeat(&self, count: u32) -> Result<Distribution> {
+        let mut result = Self::constant(0);
+        let mut base = self.clone();
+        let mut count = count;
+        while count > 0 {
+            if count & 1 == 1 {
+                result = result.add(&base)?;
+            }
+            count >>= 1;
+            if count > 0 {
+                base = base.add(&base)?;
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl Display for Distribution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (value, p) in &self.probabilities {
+            writeln!(
+                f,
+                "{:>6} {:>8.4}% {:>8.4}% {:>8.4}%",
+                value,
+                p * 100.0,
+                self.at_least(*value) * 100.0,
+                self.at_most(*value) * 100.0
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, crate::parse::RollParser};
+
+    fn close(x: f64, y: f64) -> bool {
+        (x - y).abs() < 1e-9
+    }
+
+    #[test]
+    fn check_uniform() {
+        let d6 = Distribution::uniform(1..=6);
+
+        assert!(close(d6.probability(3), 1.0 / 6.0));
+        assert!(close(d6.mean(), 3.5));
+        assert!(close(d6.variance(), 35.0 / 12.0));
+        assert!(close(d6.at_least(5), 1.0 / 3.0));
+        assert!(close(d6.at_most(2), 1.0 / 3.0));
+        assert_eq!(d6.min(), Some(1));
+        assert_eq!(d6.max(), Some(6));
+    }
+
+    #[test]
+    fn check_percentile() {
+        let d6 = Distribution::uniform(1..=6);
+
+        assert_eq!(d6.percentile(0.0), Some(1));
+        assert_eq!(d6.percentile(50.0), Some(3));
+        assert_eq!(d6.percentile(51.0), Some(4));
+        assert_eq!(d6.percentile(100.0), Some(6));
+    }
+
+    #[test]
+    fn check_repeat() -> Result<()> {
+        let d6 = Distribution::uniform(1..=6);
+        let sum = d6.repeat(3)?;
+
+        assert!(close(sum.mean(), 10.5));
+        assert!(close(sum.probability(3), 1.0 / 216.0));
+        assert!(close(sum.probability(10), 27.0 / 216.0));
+        assert_eq!(d6.repeat(0)?, Distribution::constant(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_expression() -> Result<()> {
+        let distribution = Distribution::of(&RollParser::expression("2d6+1d4+3")?)?;
+
+        assert!(close(distribution.mean(), 7.0 + 2.5 + 3.0));
+        assert_eq!(distribution.min(), Some(6));
+        assert_eq!(distribution.max(), Some(19));
+
+        let distribution = Distribution::of(&RollParser::expression("-(1d4*2)")?)?;
+
+        assert_eq!(
+            distribution.probabilities().keys().collect::<Vec<_>>(),
+            vec![&-8, &-6, &-4, &-2]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_wild() -> Result<()> {
+        let distribution = Distribution::of(&RollParser::expression("sw(d4)")?)?;
+
+        // snake eyes, then both dice under 4 without it
+        assert!(close(distribution.probability(-1), 1.0 / 24.0));
+        assert!(close(distribution.probability(0), 8.0 / 24.0));
+        assert!(close(distribution.at_least(1), 15.0 / 24.0));
+        assert!(Distribution::of(&RollParser::expression("sw(dBoost)")?).is_err());
+
+        let modified = Distribution::of(&RollParser::expression("sw(d4+1)")?)?;
+
+        // snake eyes still fails critically, and only a 2 or less on both dice misses
+        assert!(close(modified.probability(-1), 1.0 / 24.0));
+        assert!(close(modified.probability(0), 3.0 / 24.0));
+        assert!(Distribution::of(&RollParser::expression("sw(d4) + 1")?).is_err());
+        assert!(Distribution::of(&RollParser::expression("2 * -sw(d4)")?).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_expression_division() -> Result<()> {
+        let distribution = Distribution::of(&RollParser::expression("1d6/2")?)?;
+
+        assert!(close(distribution.probability(0), 1.0 / 6.0));
+        assert!(close(distribution.probability(3), 1.0 / 6.0));
+        assert!(Distribution::of(&RollParser::expression("6/(1d3-1)")?).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,882 @@
+use {
+    crate::{
+        dice::{bound::Bounded, Dice, Die},
+        roll::{
+            behaviour::{Behaviour, DiscardDirection, RerollType, DEFAULT_LIMIT},
+            outcome::{Level, Outcome, Outcomes},
+            value::ExType,
+        },
+        stats::Distribution,
+    },
+    anyhow::{anyhow, Result},
+    std::{
+        cmp::Ordering,
+        collections::{BTreeMap, HashMap},
+        ops::{Range, RangeBounds},
+    },
+};
+
+/// Explosion chains without a limit of their own are followed at most this many times when
+/// calculating a distribution, however high the global limit; rerolls past it are exact.
+pub const EXPLODE_DEPTH: usize = 20;
+
+/// Success pools score a success as this many failures, so that both can be totalled at once
+/// and told apart afterwards.
+const POOL_SPLIT: i32 = 1 << 16;
+
+/// Final state of a single die after rerolls and explosions as `(value, spent, banked)`,
+/// where `value` is the die's last face, `spent` the total of the faces an explosion
+/// superseded and `banked` their outcome score. Discards rank a die by `value + spent`.
+type DieState = BTreeMap<(i32, i32, i32), f64>;
+
+/// Distribution of the number of dice marked as critical failures and successes.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Criticals {
+    failures: Distribution,
+    successes: Distribution,
+}
+
+impl Criticals {
+    pub fn failures(&self) -> &Distribution {
+        &self.failures
+    }
+
+    pub fn successes(&self) -> &Distribution {
+        &self.successes
+    }
+}
+
+/// Exact distribution of the outcome of rolling `die` and applying `behaviours`.
+pub fn distribution(
+    die: &Die,
+    behaviours: &[Behaviour],
+    outcomes: &Outcomes,
+) -> Result<Distribution> {
+    distribution_with_limit(die, behaviours, outcomes, DEFAULT_LIMIT)
+}
+
+/// As [`distribution`], stopping any reroll or explosion without a limit of its own after
+/// `limit` iterations of a die.
+pub fn distribution_with_limit(
+    die: &Die,
+    behaviours: &[Behaviour],
+    outcomes: &Outcomes,
+    limit: usize,
+) -> Result<Distribution> {
+    let dice = die.dice();
+    let count = die.count();
+    let mut behaviours = behaviours.to_vec();
+    behaviours.sort_unstable();
+
+    if *outcomes == Outcomes::Match
+        && behaviours.iter().any(|b| {
+            matches!(
+                b,
+                Behaviour::Explode(_, ExType::Standard | ExType::Penetrating, _)
+            )
+        })
+    {
+        return Err(anyhow!(
+            "Matches over standard or penetrating explosions have no exact distribution"
+        ));
+    }
+
+    // symbol dice score the net successes on each face, as they do when rolled
+    let score = |value: i32| match (dice, outcomes) {
+        (Dice::Symbolic(symbolic), _) => symbolic.symbols(value).cancel().net(),
+        (_, Outcomes::Total | Outcomes::Match) => value,
+        (_, Outcomes::Target(point)) => point.contains(&value) as i32,
+        (_, Outcomes::Skill(skill)) => Level::of(value, *skill).value(),
+        (_, Outcomes::Hits) => Outcomes::hit(value) as i32,
+        (_, Outcomes::Pool(target, failure, _)) => {
+            target.contains(&value) as i32 * POOL_SPLIT
+                + failure.is_some_and(|f| f.contains(&value)) as i32
+        }
+    };
+
+    let state = die_state(dice, &behaviours, limit, &score)?;
+    let kept = kept(count, &behaviours)?;
+    if matches!(outcomes, Outcomes::Skill(_)) && kept.len() != 1 {
+        return Err(anyhow!(
+            "A skill roll needs a single die, not {}",
+            kept.len()
+        ));
+    }
+    if matches!(outcomes, Outcomes::Pool(..)) {
+        // failures counted past the split would be read back as successes
+        let most = state
+            .keys()
+            .map(|(value, _, banked)| (banked + score(*value)) % POOL_SPLIT)
+            .max()
+            .unwrap_or_default() as usize
+            * kept.len();
+        if most >= POOL_SPLIT as usize {
+            return Err(anyhow!(
+                "A success pool of {} can count {} failures, more than the {} it can tell apart from successes",
+                die,
+                most,
+                POOL_SPLIT - 1
+            ));
+        }
+    }
+
+    // probability of each total of faces, which ranks the die for discards, and the
+    // distribution of the die's score given that total
+    let mut faces: BTreeMap<i32, (f64, BTreeMap<i32, f64>)> = BTreeMap::new();
+    for ((value, spent, banked), p) in state {
+        let rank = value
+            .checked_add(spent)
+            .ok_or_else(|| anyhow!("Overflow ranking {}", value))?;
+        let (total, scores) = faces.entry(rank).or_insert((0.0, BTreeMap::new()));
+        *total += p;
+        let score = banked
+            .checked_add(score(value))
+            .ok_or_else(|| anyhow!("Overflow scoring {}", value))?;
+        *scores.entry(score).or_insert(0.0) += p;
+    }
+    let faces: Vec<(i32, f64, Distribution)> = faces
+        .into_iter()
+        .map(|(value, (total, scores))| {
+            let scores = scores.into_iter().map(|(s, p)| (s, p / total)).collect();
+            (value, total, Distribution::new(scores))
+        })
+        .collect();
+
+    if kept == (0..count) && *outcomes != Outcomes::Match {
+        let mut single = BTreeMap::new();
+        for (_, total, scores) in &faces {
+            for (score, p) in scores.probabilities() {
+                *single.entry(*score).or_insert(0.0) += total * p;
+            }
+        }
+        return pool(Distribution::new(single).repeat(count)?, outcomes);
+    }
+
+    pool(kept_distribution(count, kept, &faces, outcomes)?, outcomes)
+}
+
+/// Separate the successes and failures totalled for a success pool into its outcome.
+fn pool(distribution: Distribution, outcomes: &Outcomes) -> Result<Distribution> {
+    match outcomes {
+        Outcomes::Pool(_, _, exceptional) => distribution
+            .map(|v| Ok(Outcome::pool(v / POOL_SPLIT, v % POOL_SPLIT, *exceptional).value())),
+        _ => Ok(distribution),
+    }
+}
+
+/// Distribution of critical failures and successes, if `behaviours` mark any.
+pub fn criticals(die: &Die, behaviours: &[Behaviour], limit: usize) -> Result<Option<Criticals>> {
+    let dice = die.dice();
+    let mut behaviours = behaviours.to_vec();
+    behaviours.sort_unstable();
+
+    let (failure, success) = match behaviours.iter().find_map(|b| match b {
+        Behaviour::Critical(failure, success) => Some((*failure, *success)),
+        _ => None,
+    }) {
+        Some((failure, success)) => (
+            Behaviour::failure(dice, failure),
+            Behaviour::success(dice, success),
+        ),
+        None => return Ok(None),
+    };
+
+    let mut failed = 0.0;
+    let mut succeeded = 0.0;
+    for ((value, _, _), p) in die_state(dice, &behaviours, limit, &|v| v)? {
+        match (&failure, &success) {
+            (Some(f), _) if f.contains(&value) => failed += p,
+            (_, Some(s)) if s.contains(&value) => succeeded += p,
+            _ => (),
+        }
+    }
+
+    let bernoulli = |p: f64| Distribution::new(BTreeMap::from([(0, 1.0 - p), (1, p)]));
+    Ok(Some(Criticals {
+        failures: bernoulli(failed).repeat(die.count())?,
+        successes: bernoulli(succeeded).repeat(die.count())?,
+    }))
+}
+
+fn die_state(
+    dice: &Dice,
+    behaviours: &[Behaviour],
+    limit: usize,
+    score: &dyn Fn(i32) -> i32,
+) -> Result<DieState> {
+    let faces = Distribution::uniform(dice.sides());
+    let mut state: DieState = faces
+        .probabilities()
+        .iter()
+        .map(|(v, p)| ((*v, 0, 0), *p))
+        .collect();
+
+    // a percentile die keeps its units die when the tens die is rolled again
+    let tens: Vec<Distribution> = (0..10)
+        .map(|units| Distribution::uniform((0..10).map(|t| Dice::percentile(t, units))))
+        .collect();
+
+    for behaviour in behaviours {
+        match behaviour {
+            Behaviour::Reroll(point, RerollType::Repeat, own)
+                if own.is_some() || limit < EXPLODE_DEPTH =>
+            {
+                // a limited reroll is the same as rerolling once, that many times
+                if let Some(range) = Behaviour::failure(dice, *point) {
+                    for _ in 0..own.unwrap_or(limit) {
+                        state = self::reroll(state, &range, RerollType::Once, &|_| &faces)?;
+                    }
+                }
+            }
+            Behaviour::Reroll(point, reroll, _) => {
+                if let Some(range) = Behaviour::failure(dice, *point) {
+                    state = self::reroll(state, &range, *reroll, &|_| &faces)?;
+                }
+            }
+            Behaviour::RerollTens(point, reroll) => {
+                if let Some(range) = Behaviour::failure(dice, *point) {
+                    state = self::reroll(state, &range, *reroll, &|value| {
+                        &tens[(value % 10) as usize]
+                    })?;
+                }
+            }
+            Behaviour::Bonus(number) | Behaviour::Penalty(number) => {
+                // each extra tens die is an unconditional reroll keeping the better result
+                let keep = match behaviour {
+                    Behaviour::Bonus(_) => RerollType::Low,
+                    _ => RerollType::High,
+                };
+                let all = Bounded::from_range(..);
+                for _ in 0..*number {
+                    state = self::reroll(state, &all, keep, &|value| &tens[(value % 10) as usize])?;
+                }
+            }
+            Behaviour::Explode(point, explode, own) => {
+                if let Some(range) = Behaviour::success(dice, *point) {
+                    let depth = own.unwrap_or(limit.min(EXPLODE_DEPTH));
+                    state = self::explode(state, &range, *explode, depth, dice, &faces, score)?;
+                }
+            }
+            _ => (),
+        }
+    }
+    Ok(state)
+}
+
+/// Reroll the faces in `range`, where `faces` gives the faces a value may be rerolled into.
+fn reroll<'a>(
+    state: DieState,
+    range: &Bounded,
+    reroll: RerollType,
+    faces: &dyn Fn(i32) -> &'a Distribution,
+) -> Result<DieState> {
+    let mut result: DieState = BTreeMap::new();
+    for ((value, spent, banked), p) in state {
+        if !range.contains(&value) {
+            *result.entry((value, spent, banked)).or_insert(0.0) += p;
+            continue;
+        }
+        let faces = faces(value);
+        let kept: f64 = faces
+            .probabilities()
+            .iter()
+            .filter(|(v, _)| !range.contains(*v))
+            .map(|(_, p)| p)
+            .sum();
+        if reroll == RerollType::Repeat && kept == 0.0 {
+            return Err(anyhow!("Reroll on {} never finishes", range));
+        }
+        for (face, q) in faces.probabilities() {
+            // a repeated reroll only ends on a face outside the range
+            let (face, q) = match reroll {
+                RerollType::Repeat if range.contains(face) => continue,
+                RerollType::Repeat => (*face, q / kept),
+                RerollType::Once => (*face, *q),
+                RerollType::High => (value.max(*face), *q),
+                RerollType::Low => (value.min(*face), *q),
+            };
+            *result.entry((face, spent, banked)).or_insert(0.0) += p * q;
+        }
+    }
+    Ok(result)
+}
+
+fn explode(
+    state: DieState,
+    range: &Bounded,
+    explode: ExType,
+    depth: usize,
+    dice: &Dice,
+    faces: &Distribution,
+    score: &dyn Fn(i32) -> i32,
+) -> Result<DieState> {
+    let start = *dice.faces().start();
+    let overflow = || anyhow!("Overflow exploding {}", dice);
+
+    // (value, spent, banked, last roll) for chains that may still explode
+    let mut pending: BTreeMap<(i32, i32, i32, i32), f64> = state
+        .into_iter()
+        .map(|((value, spent, banked), p)| ((value, spent, banked, value), p))
+        .collect();
+    let mut result: DieState = BTreeMap::new();
+
+    for _ in 0..depth {
+        let mut next = BTreeMap::new();
+        for ((value, spent, banked, last), p) in pending {
+            if !range.contains(&last) {
+                *result.entry((value, spent, banked)).or_insert(0.0) += p;
+                continue;
+            }
+            for (r, pr) in faces.probabilities() {
+                let key = match explode {
+                    ExType::Standard | ExType::Penetrating => {
+                        let r = match explode {
+                            ExType::Penetrating => (r - 1).max(start),
+                            _ => *r,
+                        };
+                        (
+                            r,
+                            spent.checked_add(value).ok_or_else(overflow)?,
+                            banked.checked_add(score(value)).ok_or_else(overflow)?,
+                            r,
+                        )
+                    }
+                    ExType::Compound => (
+                        value.checked_add(*r).ok_or_else(overflow)?,
+                        spent,
+                        banked,
+                        *r,
+                    ),
+                };
+                *next.entry(key).or_insert(0.0) += p * pr;
+            }
+        }
+        pending = next;
+    }
+    for ((value, spent, banked, _), p) in pending {
+        *result.entry((value, spent, banked)).or_insert(0.0) += p;
+    }
+    Ok(result)
+}
+
+/// Positions, in ascending order of face, of the dice left after keeping and dropping.
+fn kept(count: u32, behaviours: &[Behaviour]) -> Result<Range<u32>> {
+    let mut kept = 0..count;
+    for behaviour in behaviours {
+        let (number, from_low) = match behaviour {
+            Behaviour::Keep(number, DiscardDirection::High) => {
+                (kept.len().saturating_sub(*number), true)
+            }
+            Behaviour::Keep(number, DiscardDirection::Low) => {
+                (kept.len().saturating_sub(*number), false)
+            }
+            Behaviour::Drop(number, DiscardDirection::Low) => (*number, true),
+            Behaviour::Drop(number, DiscardDirection::High) => (*number, false),
+            _ => continue,
+        };
+        let number = u32::try_from(number)?.min(kept.end - kept.start);
+        kept = if from_low {
+            kept.start + number..kept.end
+        } else {
+            kept.start..kept.end - number
+        };
+    }
+    Ok(kept)
+}
+
+fn kept_distribution(
+    count: u32,
+    kept: Range<u32>,
+    faces: &[(i32, f64, Distribution)],
+    outcomes: &Outcomes,
+) -> Result<Distribution> {
+    // dice assigned so far (in ascending order of face) => weighted distribution of the result
+    let mut state: HashMap<u32, BTreeMap<i32, f64>> =
+        HashMap::from([(0, BTreeMap::from([(0, 1.0)]))]);
+
+    for (value, p, scores) in faces {
+        let mut powers = vec![Distribution::constant(0)];
+        let mut next: HashMap<u32, BTreeMap<i32, f64>> = HashMap::new();
+        for (assigned, weights) in state {
+            let mut combinations = 1.0;
+            for m in 0..=count - assigned {
+                if m > 0 {
+                    combinations *= (count - assigned - m + 1) as f64 / m as f64;
+                }
+                let weight = combinations * p.powi(m as i32);
+                if weight == 0.0 && m > 0 {
+                    break;
+                }
+                let overlap = (assigned + m)
+                    .min(kept.end)
+                    .saturating_sub(assigned.max(kept.start));
+                let entry = next.entry(assigned + m).or_default();
+                match outcomes {
+                    Outcomes::Match => {
+                        let shift = (overlap > 1) as i32;
+                        for (result, w) in &weights {
+                            *entry.entry(result + shift).or_insert(0.0) += w * weight;
+                        }
+                    }
+                    _ => {
+                        while powers.len() <= overlap as usize {
+                            let power = powers[powers.len() - 1].add(scores)?;
+                            powers.push(power);
+                        }
+                        for (result, w) in &weights {
+                            for (score, ps) in powers[overlap as usize].probabilities() {
+                                let total = result
+                                    .checked_add(*score)
+                                    .ok_or_else(|| anyhow!("Overflow totalling face {}", value))?;
+                                *entry.entry(total).or_insert(0.0) += w * weight * ps;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        state = next;
+    }
+
+    let result = state.remove(&count).unwrap_or_default();
+    let total: f64 = result.values().sum();
+    match total.partial_cmp(&0.0) {
+        Some(Ordering::Greater) => Ok(Distribution::new(
+            result.into_iter().map(|(v, p)| (v, p / total)).collect(),
+        )),
+        _ => Err(anyhow!("Unable to calculate distribution")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::{dice::narrative::Narrative, roll::behaviour::DiscardDirection::*},
+    };
+
+    fn close(x: f64, y: f64) -> bool {
+        (x - y).abs() < 1e-9
+    }
+
+    #[test]
+    fn check_sum() -> Result<()> {
+        let result = distribution(&Die::new(Dice::D6, 2), &[], &Outcomes::Total)?;
+
+        assert!(close(result.probability(7), 6.0 / 36.0));
+        assert!(close(result.mean(), 7.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_keep_highest() -> Result<()> {
+        let result = distribution(
+            &Die::new(Dice::D6, 4),
+            &[Behaviour::Keep(3, High)],
+            &Outcomes::Total,
+        )?;
+
+        assert!(close(result.probability(18), 21.0 / 1296.0));
+        assert!(close(result.probability(3), 1.0 / 1296.0));
+        assert!((result.mean() - 12.2446).abs() < 1e-4);
+        assert!(close(result.at_least(15), 300.0 / 1296.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_advantage() -> Result<()> {
+        let advantage = distribution(
+            &Die::new(Dice::D20, 2),
+            &[Behaviour::Keep(1, High)],
+            &Outcomes::Total,
+        )?;
+        let disadvantage = distribution(
+            &Die::new(Dice::D20, 2),
+            &[Behaviour::Drop(1, High)],
+            &Outcomes::Total,
+        )?;
+
+        assert!(close(advantage.probability(20), 39.0 / 400.0));
+        assert!(close(disadvantage.probability(20), 1.0 / 400.0));
+        assert!(close(advantage.mean(), 13.825));
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_reroll() -> Result<()> {
+        let repeat = distribution(
+            &Die::new(Dice::D6, 1),
+            &[Behaviour::Reroll(None, RerollType::Repeat, None)],
+            &Outcomes::Total,
+        )?;
+        let once = distribution(
+            &Die::new(Dice::D6, 1),
+            &[Behaviour::Reroll(None, RerollType::Once, None)],
+            &Outcomes::Total,
+        )?;
+
+        assert!(close(repeat.probability(1), 0.0));
+        assert!(close(repeat.probability(6), 1.0 / 5.0));
+        assert!(close(once.probability(1), 1.0 / 36.0));
+        assert!(close(once.probability(6), 7.0 / 36.0));
+
+        let point = Some(Bounded::range_to(2));
+        let high = distribution(
+            &Die::new(Dice::D6, 1),
+            &[Behaviour::Reroll(point, RerollType::High, None)],
+            &Outcomes::Total,
+        )?;
+        let low = distribution(
+            &Die::new(Dice::D6, 1),
+            &[Behaviour::Reroll(point, RerollType::Low, None)],
+            &Outcomes::Total,
+        )?;
+
+        assert!(close(high.probability(1), 1.0 / 36.0));
+        assert!(close(high.probability(2), 3.0 / 36.0));
+        assert!(close(high.probability(6), 8.0 / 36.0));
+        assert!(close(low.probability(1), 7.0 / 36.0));
+        assert!(close(low.probability(2), 5.0 / 36.0));
+        assert!(close(low.probability(6), 1.0 / 6.0));
+
+        assert!(distribution(
+            &Die::new(Dice::D6, 1),
+            &[Behaviour::Reroll(
+                Some(Bounded::from_range(..=6)),
+                RerollType::Repeat,
+                None,
+            )],
+            &Outcomes::Total,
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_explode() -> Result<()> {
+        let standard = distribution(
+            &Die::new(Dice::D6, 1),
+            &[Behaviour::Explode(None, ExType::Standard, None)],
+            &Outcomes::Total,
+        )?;
+        let compound = distribution(
+            &Die::new(Dice::D6, 1),
+            &[Behaviour::Explode(None, ExType::Compound, None)],
+            &Outcomes::Total,
+        )?;
+
+        assert!(close(standard.probability(6), 0.0));
+        assert!(close(standard.probability(7), 1.0 / 36.0));
+        assert!((standard.mean() - 4.2).abs() < 1e-9);
+        assert_eq!(standard, compound);
+
+        let penetrating = distribution(
+            &Die::new(Dice::D6, 1),
+            &[Behaviour::Explode(None, ExType::Penetrating, None)],
+            &Outcomes::Total,
+        )?;
+
+        assert!(close(penetrating.probability(6), 0.0));
+        assert!(close(penetrating.probability(7), 2.0 / 36.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_keep_exploded() -> Result<()> {
+        let explode = Behaviour::Explode(None, ExType::Standard, None);
+        let keep = distribution(
+            &Die::new(Dice::D6, 2),
+            &[explode, Behaviour::Keep(1, High)],
+            &Outcomes::Total,
+        )?;
+        let drop = distribution(
+            &Die::new(Dice::D6, 2),
+            &[explode, Behaviour::Drop(1, Low)],
+            &Outcomes::Total,
+        )?;
+
+        // an exploded die totals at least 7 and is always kept over a die that did not explode
+        assert!(close(keep.at_least(7), 11.0 / 36.0));
+        assert!(close(keep.probability(5), 9.0 / 36.0));
+        assert_eq!(keep, drop);
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_limit() -> Result<()> {
+        let explode = distribution(
+            &Die::new(Dice::D6, 1),
+            &[Behaviour::Explode(None, ExType::Standard, Some(1))],
+            &Outcomes::Total,
+        )?;
+        let reroll = distribution(
+            &Die::new(Dice::D6, 1),
+            &[Behaviour::Reroll(None, RerollType::Repeat, Some(2))],
+            &Outcomes::Total,
+        )?;
+
+        assert!(close(explode.probability(12), 1.0 / 36.0));
+        assert!(close(explode.probability(13), 0.0));
+        assert!(close(reroll.probability(1), 1.0 / 216.0));
+
+        // the global limit stops behaviours without a limit of their own
+        let explode = distribution_with_limit(
+            &Die::new(Dice::D6, 1),
+            &[Behaviour::Explode(None, ExType::Standard, None)],
+            &Outcomes::Total,
+            1,
+        )?;
+        let reroll = distribution_with_limit(
+            &Die::new(Dice::D6, 1),
+            &[Behaviour::Reroll(None, RerollType::Repeat, None)],
+            &Outcomes::Total,
+            2,
+        )?;
+
+        assert_eq!(explode.max(), Some(12));
+        assert!(close(reroll.probability(1), 1.0 / 216.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_reroll_tens() -> Result<()> {
+        let point = Some(Bounded::range_from(50));
+        let once = distribution(
+            &Die::new(Dice::Percentile, 1),
+            &[Behaviour::RerollTens(point, RerollType::Once)],
+            &Outcomes::Total,
+        )?;
+        let repeat = distribution(
+            &Die::new(Dice::Percentile, 1),
+            &[Behaviour::RerollTens(point, RerollType::Repeat)],
+            &Outcomes::Total,
+        )?;
+
+        assert!(close(once.probability(7), 15.0 / 1000.0));
+        assert!(close(once.probability(57), 5.0 / 1000.0));
+        assert!(close(repeat.probability(57), 0.0));
+        assert!(close(repeat.probability(100), 0.0));
+        assert!(close(repeat.probability(10), 10.0 / 400.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_bonus_penalty() -> Result<()> {
+        let skill = Outcomes::Skill(50);
+        let plain = distribution(&Die::new(Dice::D100, 1), &[], &skill)?;
+        let bonus = distribution(&Die::new(Dice::D100, 1), &[Behaviour::Bonus(1)], &skill)?;
+        let penalty = distribution(
+            &Die::new(Dice::Percentile, 1),
+            &[Behaviour::Penalty(2)],
+            &Outcomes::Total,
+        )?;
+
+        // 02 to 10 are extreme, 11 to 25 hard and 100 the only fumble
+        assert!(close(plain.probability(3), 9.0 / 100.0));
+        assert!(close(plain.probability(2), 15.0 / 100.0));
+        assert!(close(plain.probability(-1), 1.0 / 100.0));
+        // a bonus die fumbles only when both tens dice show 00
+        assert!(close(bonus.probability(-1), 1.0 / 1000.0));
+        assert!(close(bonus.probability(4), 19.0 / 1000.0));
+        assert!(close(penalty.probability(100), 271.0 / 10000.0));
+        assert!(distribution(&Die::new(Dice::D100, 2), &[], &skill).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_pool() -> Result<()> {
+        let pool = Outcomes::Pool(Bounded::range_from(8), Some(Bounded::range_to(1)), None);
+        let single = distribution(&Die::new(Dice::D10, 1), &[], &pool)?;
+        let pair = distribution(&Die::new(Dice::D10, 2), &[], &pool)?;
+
+        assert!(close(single.probability(1), 3.0 / 10.0));
+        assert!(close(single.probability(-1), 1.0 / 10.0));
+        // a botch needs at least one 1 and no successes
+        assert!(close(pair.probability(-1), 13.0 / 100.0));
+        // a success and a 1 cancel out, as do two dice with neither
+        assert!(close(pair.probability(0), 42.0 / 100.0));
+        assert!(close(pair.probability(2), 9.0 / 100.0));
+
+        let again = distribution(
+            &Die::new(Dice::D10, 1),
+            &[Behaviour::Explode(
+                Some(Bounded::range_of(10)),
+                ExType::Standard,
+                None,
+            )],
+            &pool,
+        )?;
+
+        // a 1 after a ten cancels the ten's success without botching
+        assert!(close(again.probability(-1), 1.0 / 10.0));
+        assert!(close(again.probability(0), 61.0 / 100.0));
+        assert!(distribution(&Die::new(Dice::D10, 1 << 16), &[], &pool).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_hits() -> Result<()> {
+        let hits = distribution(&Die::new(Dice::D6, 4), &[], &Outcomes::Hits)?;
+        let edge = distribution(
+            &Die::new(Dice::D6, 1),
+            &[Behaviour::Explode(None, ExType::Standard, None)],
+            &Outcomes::Hits,
+        )?;
+
+        assert!(close(hits.probability(0), 16.0 / 81.0));
+        assert!(close(hits.mean(), 4.0 / 3.0));
+        // a 5, or a 6 followed by a miss
+        assert!(close(edge.probability(1), 10.0 / 36.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_custom() -> Result<()> {
+        let sum = distribution(
+            &Die::new(Dice::Custom(vec![-2, 0, 0, 2]), 2),
+            &[],
+            &Outcomes::Total,
+        )?;
+        let target = distribution(
+            &Die::new(Dice::Custom(vec![1, 1, 2, 3, 5, 8]), 1),
+            &[],
+            &Outcomes::Target(Bounded::range_from(5)),
+        )?;
+
+        assert!(close(sum.probability(0), 6.0 / 16.0));
+        assert!(close(sum.probability(4), 1.0 / 16.0));
+        assert!(close(sum.probability(1), 0.0));
+        assert!(close(sum.mean(), 0.0));
+        assert!(close(target.probability(1), 2.0 / 6.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_digits() -> Result<()> {
+        let d66 = distribution(&Die::new(Dice::Digits(6, 2), 1), &[], &Outcomes::Total)?;
+
+        assert!(close(d66.probability(35), 1.0 / 36.0));
+        assert!(close(d66.probability(17), 0.0));
+        assert!(close(d66.mean(), 38.5));
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_narrative() -> Result<()> {
+        let boost = distribution(
+            &Die::new(Dice::from(Narrative::Boost), 1),
+            &[],
+            &Outcomes::Total,
+        )?;
+        let challenge = distribution(
+            &Die::new(Dice::from(Narrative::Challenge), 2),
+            &[],
+            &Outcomes::Total,
+        )?;
+
+        assert!(close(boost.probability(1), 2.0 / 6.0));
+        assert!(close(boost.probability(0), 4.0 / 6.0));
+        assert!(close(challenge.probability(-4), 4.0 / 144.0));
+        assert!(close(challenge.mean(), -2.0 * 9.0 / 12.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_target() -> Result<()> {
+        let result = distribution(
+            &Die::new(Dice::D10, 5),
+            &[],
+            &Outcomes::Target(Bounded::range_from(8)),
+        )?;
+
+        assert!(close(result.probability(0), 0.7f64.powi(5)));
+        assert!(close(result.mean(), 1.5));
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_target_explode() -> Result<()> {
+        let result = distribution(
+            &Die::new(Dice::D10, 1),
+            &[Behaviour::Explode(None, ExType::Standard, None)],
+            &Outcomes::Target(Bounded::range_from(8)),
+        )?;
+
+        assert!(close(result.probability(0), 0.7));
+        // a ten then an eight or nine, or two tens then a miss
+        assert!(close(result.probability(2), 0.1 * 0.2 + 0.1 * 0.1 * 0.7));
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_match() -> Result<()> {
+        let result = distribution(&Die::new(Dice::D6, 2), &[], &Outcomes::Match)?;
+
+        assert!(close(result.probability(1), 1.0 / 6.0));
+
+        let result = distribution(
+            &Die::new(Dice::D6, 3),
+            &[Behaviour::Keep(2, High)],
+            &Outcomes::Match,
+        )?;
+
+        // pairs kept from three dice, (a, a, b) with b < a plus (a, a, a)
+        assert!(close(result.probability(1), (3.0 * 15.0 + 6.0) / 216.0));
+        assert!(distribution(
+            &Die::new(Dice::D6, 3),
+            &[Behaviour::Explode(None, ExType::Standard, None)],
+            &Outcomes::Match,
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_criticals() -> Result<()> {
+        let result = criticals(
+            &Die::new(Dice::D20, 2),
+            &[Behaviour::Critical(None, Some(Bounded::from_range(19..)))],
+            DEFAULT_LIMIT,
+        )?
+        .unwrap();
+
+        assert!(close(result.successes().probability(2), 0.01));
+        assert!(close(result.failures().at_least(1), 1.0 - 0.95f64.powi(2)));
+        assert_eq!(
+            criticals(&Die::new(Dice::D20, 2), &[], DEFAULT_LIMIT)?,
+            None
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_kept() -> Result<()> {
+        assert_eq!(kept(4, &[Behaviour::Keep(3, High)])?, 1..4);
+        assert_eq!(kept(4, &[Behaviour::Keep(3, Low)])?, 0..3);
+        assert_eq!(kept(4, &[Behaviour::Drop(1, Low)])?, 1..4);
+        assert_eq!(
+            kept(6, &[Behaviour::Keep(4, High), Behaviour::Drop(2, High)])?,
+            2..4
+        );
+
+        Ok(())
+    }
+}
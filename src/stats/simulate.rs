@@ -0,0 +1,138 @@
+use {
+    crate::{roll::expression::Expression, seeded, stats::Distribution},
+    anyhow::{anyhow, Result},
+    std::{
+        collections::BTreeMap,
+        fmt::{self, Display},
+    },
+};
+
+/// Width, in characters, of the longest bar in a rendered histogram.
+const BAR_WIDTH: u64 = 50;
+
+/// Frequency of each result over a number of simulated rolls.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Histogram {
+    counts: BTreeMap<i32, u64>,
+    runs: u64,
+}
+
+impl Histogram {
+    pub fn counts(&self) -> &BTreeMap<i32, u64> {
+        &self.counts
+    }
+
+    pub fn runs(&self) -> u64 {
+        self.runs
+    }
+
+    /// Observed frequencies as an empirical distribution.
+    pub fn distribution(&self) -> Distribution {
+        Distribution::new(
+            self.counts
+                .iter()
+                .map(|(v, c)| (*v, *c as f64 / self.runs as f64))
+                .collect(),
+        )
+    }
+}
+
+impl Display for Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let most = self
+            .counts()
+            .values()
+            .max()
+            .copied()
+            .unwrap_or_default()
+            .max(1);
+        for (value, count) in self.counts() {
+            let bar = (count * BAR_WIDTH + most / 2) / most;
+            writeln!(
+                f,
+                "{:>6} {:>8.4}% {}",
+                value,
+                *count as f64 * 100.0 / self.runs as f64,
+                "#".repeat(bar as usize)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Evaluate `expression` `runs` times from a generator seeded with `seed`, counting each result.
+/// Rerolls and explosions without a limit of their own stop after `limit` iterations of a die.
+pub fn simulate(expression: &Expression, runs: u64, seed: u64, limit: usize) -> Result<Histogram> {
+    if runs == 0 {
+        return Err(anyhow!("Simulation needs at least one run"));
+    }
+
+    let mut rng = seeded(seed);
+    let mut counts = BTreeMap::new();
+    for _ in 0..runs {
+        let evaluation = expression.evaluate_with_limit(limit, &mut rng)?;
+        *counts.entry(evaluation.total()).or_insert(0) += 1;
+    }
+    Ok(Histogram { counts, runs })
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::{parse::RollParser, roll::behaviour::DEFAULT_LIMIT},
+    };
+
+    #[test]
+    fn check_simulate() -> Result<()> {
+        let expression = RollParser::expression("4d6k3")?;
+        let histogram = simulate(&expression, 10_000, 42, DEFAULT_LIMIT)?;
+
+        assert_eq!(histogram.runs(), 10_000);
+        assert_eq!(histogram.counts().values().sum::<u64>(), 10_000);
+        assert!(histogram.counts().keys().all(|v| (3..=18).contains(v)));
+        assert!((histogram.distribution().mean() - 12.24).abs() < 0.2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_simulate_is_seeded() -> Result<()> {
+        let expression = RollParser::expression("3d6!+1d4")?;
+
+        assert_eq!(
+            simulate(&expression, 1_000, 7, DEFAULT_LIMIT)?,
+            simulate(&expression, 1_000, 7, DEFAULT_LIMIT)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_simulate_outcome() -> Result<()> {
+        let expression = RollParser::expression("5d10t8")?;
+        let histogram = simulate(&expression, 1_000, 1, DEFAULT_LIMIT)?;
+
+        assert!(histogram.counts().keys().all(|v| (0..=5).contains(v)));
+        assert!(simulate(&expression, 0, 1, DEFAULT_LIMIT).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_display() {
+        let histogram = Histogram {
+            counts: BTreeMap::from([(1, 1), (2, 4)]),
+            runs: 5,
+        };
+
+        assert_eq!(
+            histogram.to_string(),
+            format!(
+                "     1  20.0000% {}\n     2  80.0000% {}\n",
+                "#".repeat(13),
+                "#".repeat(50)
+            )
+        );
+    }
+}